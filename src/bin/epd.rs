@@ -0,0 +1,251 @@
+use queenfish::board::bishop_magic::init_bishop_magics;
+use queenfish::board::rook_magic::init_rook_magics;
+use queenfish::board::search::MATE_SCORE;
+use queenfish::board::{Board, Engine, Move, SearchConfig, SearchLimits};
+use std::time::{Duration, Instant};
+
+const USAGE: &str =
+    "usage: epd <file.epd> [--depth N] [--nodes N] [--movetime MS] (default --depth 6)";
+
+struct EpdEntry {
+    line: usize,
+    board: Board,
+    id: String,
+    best_moves: Vec<Move>,
+    avoid_moves: Vec<Move>,
+    direct_mate: Option<u32>,
+} //
+
+/// Splits the operation section on `;`, ignoring separators inside quoted operands.
+fn split_operations(operations: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for c in operations.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            ';' if !in_quotes => {
+                result.push(current.trim().to_string());
+                current.clear();
+            }
+            _ => current.push(c),
+        }
+    }
+    if !current.trim().is_empty() {
+        result.push(current.trim().to_string());
+    }
+
+    result
+} //
+
+fn parse_moves(operands: &str, board: &Board) -> Result<Vec<Move>, String> {
+    operands
+        .split_whitespace()
+        .map(|san| Move::from_san(san, board).map_err(|err| err.to_string()))
+        .collect()
+} //
+
+fn parse_epd_line(line: &str, line_number: usize) -> Result<EpdEntry, String> {
+    let fields = line.split_whitespace().take(4).collect::<Vec<_>>();
+    if fields.len() < 4 {
+        return Err("expected 4 position fields".to_string());
+    }
+
+    // The four position fields are followed by the operations, skip past them
+    let mut rest = line.trim_start();
+    for _ in 0..4 {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        rest = rest[end..].trim_start();
+    }
+
+    let mut entry = EpdEntry {
+        line: line_number,
+        board: Board::from_fen(&fields.join(" ")).map_err(|err| err.to_string())?,
+        id: format!("line {line_number}"),
+        best_moves: Vec::new(),
+        avoid_moves: Vec::new(),
+        direct_mate: None,
+    };
+
+    for operation in split_operations(rest) {
+        let (opcode, operands) = operation
+            .split_once(char::is_whitespace)
+            .unwrap_or((operation.as_str(), ""));
+        let operands = operands.trim();
+
+        match opcode {
+            "bm" => entry.best_moves = parse_moves(operands, &entry.board)?,
+            "am" => entry.avoid_moves = parse_moves(operands, &entry.board)?,
+            "id" => entry.id = operands.trim_matches('"').to_string(),
+            "dm" => {
+                entry.direct_mate = Some(
+                    operands
+                        .parse()
+                        .map_err(|_| format!("invalid dm operand '{operands}'"))?,
+                )
+            }
+            "hmvc" => {
+                entry.board.halfmove_clock = operands.parse().unwrap_or(0);
+            }
+            "fmvn" => {
+                entry.board.fullmove_number = operands.parse().unwrap_or(1);
+            }
+            _ => (),
+        }
+    }
+
+    Ok(entry)
+} //
+
+fn main() {
+    init_bishop_magics();
+    init_rook_magics();
+
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    let mut path = None;
+    let mut depth = None;
+    let mut nodes = None;
+    let mut movetime = None;
+
+    let mut idx = 0;
+    while idx < args.len() {
+        let value = args.get(idx + 1).and_then(|value| value.parse::<u64>().ok());
+        match args[idx].as_str() {
+            "--depth" => depth = value,
+            "--nodes" => nodes = value,
+            "--movetime" => movetime = value,
+            arg if !arg.starts_with("--") && path.is_none() => {
+                path = Some(arg.to_string());
+                idx += 1;
+                continue;
+            }
+            _ => {
+                eprintln!("{USAGE}");
+                std::process::exit(2);
+            }
+        }
+        if value.is_none() {
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+        idx += 2;
+    }
+
+    let Some(path) = path else {
+        eprintln!("{USAGE}");
+        std::process::exit(2);
+    };
+
+    let contents = std::fs::read_to_string(&path).unwrap_or_else(|err| {
+        eprintln!("cannot read {path}: {err}");
+        std::process::exit(2);
+    });
+
+    let limits = SearchLimits {
+        depth: match (depth, nodes, movetime) {
+            (None, None, None) => Some(6),
+            (depth, _, _) => depth.map(|depth| depth as i32),
+        },
+        nodes,
+        movetime: movetime.map(Duration::from_millis),
+        ..Default::default()
+    };
+    let mut engine = Engine::new(SearchConfig {
+        own_book: false,
+        print_info: false,
+        ..Default::default()
+    });
+
+    let mut solved = 0;
+    let mut failed = 0;
+    let mut skipped = 0;
+
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut entry = match parse_epd_line(line, index + 1) {
+            Ok(entry) => entry,
+            Err(err) => {
+                println!("line {}: skipped, {err}", index + 1);
+                skipped += 1;
+                continue;
+            }
+        };
+
+        if !entry.board.game_status().has_legal_moves() {
+            println!("{}: skipped, no legal moves", entry.id);
+            skipped += 1;
+            continue;
+        }
+
+        let start = Instant::now();
+        engine.new_game();
+        // A direct mate is searched for up to its own depth
+        let entry_limits = match entry.direct_mate {
+            Some(mate) => SearchLimits {
+                depth: None,
+                mate: Some(mate),
+                ..limits
+            },
+            None => limits,
+        };
+        let result = engine.search(&entry.board, &entry_limits);
+        let best = result
+            .best_move
+            .expect("positions without legal moves are skipped");
+        let elapsed = start.elapsed().as_millis();
+
+        let mut is_solved = true;
+        if !entry.best_moves.is_empty() {
+            is_solved &= entry.best_moves.contains(&best);
+        }
+        if !entry.avoid_moves.is_empty() {
+            is_solved &= !entry.avoid_moves.contains(&best);
+        }
+        if let Some(mate) = entry.direct_mate {
+            is_solved &= result.score >= MATE_SCORE - (2 * mate as i32 - 1);
+        }
+
+        let expected = entry
+            .best_moves
+            .iter()
+            .map(|mv| format!("bm {}", mv.to_san(&entry.board)))
+            .chain(
+                entry
+                    .avoid_moves
+                    .iter()
+                    .map(|mv| format!("am {}", mv.to_san(&entry.board))),
+            )
+            .chain(entry.direct_mate.map(|mate| format!("dm {mate}")))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        println!(
+            "{} (line {}): {} found {} [{}] in {elapsed} ms",
+            entry.id,
+            entry.line,
+            if is_solved { "solved" } else { "FAILED" },
+            best.to_san(&entry.board),
+            expected,
+        );
+
+        if is_solved {
+            solved += 1;
+        } else {
+            failed += 1;
+        }
+    }
+
+    println!(
+        "solved {solved}/{} failed {failed} skipped {skipped}",
+        solved + failed
+    );
+} //
@@ -1,15 +1,249 @@
-fn main() {
-    use queenfish::board::Board;
+use queenfish::board::Board;
+use queenfish::board::bishop_magic::init_bishop_magics;
+use queenfish::board::rook_magic::init_rook_magics;
+use std::time::Instant;
+
+const USAGE: &str =
+    "usage: perft [--fen FEN] [--depth N] [--divide] [--pseudo] | perft --suite [--pseudo]";
+
+// (name, fen, depth, expected nodes)
+const SUITE: [(&str, &str, i32, i64); 20] = [
+    (
+        "startpos",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        5,
+        4_865_609,
+    ),
+    (
+        "kiwipete",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        4,
+        4_085_603,
+    ),
+    (
+        "position 3",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        6,
+        11_030_083,
+    ),
+    (
+        "position 4",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        4,
+        422_333,
+    ),
+    (
+        "position 4 mirrored",
+        "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1",
+        4,
+        422_333,
+    ),
+    (
+        "position 5",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        4,
+        2_103_487,
+    ),
+    (
+        "position 6",
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        4,
+        3_894_594,
+    ),
+    (
+        "promotions",
+        "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+        5,
+        3_605_103,
+    ),
+    (
+        "illegal en passant, pinned pawn",
+        "3k4/3p4/8/K1P4r/8/8/8/8 b - - 0 1",
+        6,
+        1_134_888,
+    ),
+    (
+        "illegal en passant, discovered check",
+        "8/8/4k3/8/2p5/8/B2P2K1/8 w - - 0 1",
+        6,
+        1_015_133,
+    ),
+    (
+        "en passant gives check",
+        "8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1",
+        6,
+        1_440_467,
+    ),
+    (
+        "short castling gives check",
+        "5k2/8/8/8/8/8/8/4K2R w K - 0 1",
+        6,
+        661_072,
+    ),
+    (
+        "long castling gives check",
+        "3k4/8/8/8/8/8/8/R3K3 w Q - 0 1",
+        6,
+        803_711,
+    ),
+    (
+        "castling rights loss",
+        "r3k2r/1b4bq/8/8/8/8/7B/R3K2R w KQkq - 0 1",
+        4,
+        1_274_206,
+    ),
+    (
+        "castling prevented",
+        "r3k2r/8/3Q4/8/8/5q2/8/R3K2R b KQkq - 0 1",
+        4,
+        1_720_476,
+    ),
+    (
+        "promote out of check",
+        "2K2r2/4P3/8/8/8/8/8/3k4 w - - 0 1",
+        6,
+        3_821_001,
+    ),
+    (
+        "discovered check",
+        "8/8/1P2K3/8/2n5/1q6/8/5k2 b - - 0 1",
+        5,
+        1_004_658,
+    ),
+    (
+        "promote to give check",
+        "4k3/1P6/8/8/8/8/K7/8 w - - 0 1",
+        6,
+        217_342,
+    ),
+    (
+        "under promote to give check",
+        "8/P1k5/K7/8/8/8/8/8 w - - 0 1",
+        6,
+        92_683,
+    ),
+    ("self stalemate", "K1k5/8/P7/8/8/8/8/8 w - - 0 1", 6, 2_217),
+];
+
+/// Leaf nodes below `board`, with the legal generator or with pseudo-legal moves
+/// made and unmade to find the legal ones.
+fn count(board: &mut Board, depth: i32, pseudo: bool) -> i64 {
+    match pseudo {
+        true => board.perft(0, depth),
+        false => board.perft_legal(depth),
+    }
+} //
+
+fn run_suite(pseudo: bool) -> bool {
+    let mut failures = 0;
+    let start = Instant::now();
+
+    for (name, fen, depth, expected) in SUITE {
+        let mut board = Board::from_fen(fen).expect("suite FEN is valid");
+        let nodes = count(&mut board, depth, pseudo);
+
+        if nodes == expected {
+            println!("ok      {name}: depth {depth} nodes {nodes}");
+        } else {
+            println!("FAILED  {name}: depth {depth} nodes {nodes}, expected {expected}");
+            failures += 1;
+        }
+    }
+
+    println!(
+        "{} passed, {failures} failed in {} ms",
+        SUITE.len() - failures,
+        start.elapsed().as_millis()
+    );
 
-    use queenfish::board::bishop_magic::init_bishop_magics;
-    use queenfish::board::rook_magic::init_rook_magics;
+    failures == 0
+} //
 
+fn main() {
     init_rook_magics();
     init_bishop_magics();
 
-    let mut board = Board::new();
-    // board.load_from_fen("1qr1k2r/1p2bp2/pBn1p3/P2pPbpp/5P2/2P1QBPP/1P1N3R/R4K2 b k -");
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    let mut fen = None;
+    let mut depth = 3;
+    let mut divide = false;
+    let mut suite = false;
+    let mut pseudo = false;
+
+    let mut idx = 0;
+    while idx < args.len() {
+        match args[idx].as_str() {
+            "--fen" => {
+                // Accept the FEN either quoted or spread over several arguments
+                let end = args[idx + 1..]
+                    .iter()
+                    .position(|arg| arg.starts_with("--"))
+                    .map_or(args.len(), |offset| idx + 1 + offset);
+                fen = Some(args[idx + 1..end].join(" "));
+                idx = end;
+            }
+            "--depth" => {
+                let value = args.get(idx + 1).and_then(|value| value.parse::<u8>().ok());
+                depth = match value.filter(|&depth| depth >= 1) {
+                    Some(depth) => i32::from(depth),
+                    None => {
+                        eprintln!("{USAGE}");
+                        std::process::exit(2);
+                    }
+                };
+                idx += 2;
+            }
+            "--divide" => {
+                divide = true;
+                idx += 1;
+            }
+            "--suite" => {
+                suite = true;
+                idx += 1;
+            }
+            "--pseudo" => {
+                pseudo = true;
+                idx += 1;
+            }
+            _ => {
+                eprintln!("{USAGE}");
+                std::process::exit(2);
+            }
+        }
+    }
+
+    if suite {
+        let passed = run_suite(pseudo);
+        std::process::exit(if passed { 0 } else { 1 });
+    }
+
+    let mut board = match fen {
+        Some(fen) => Board::from_fen(&fen).unwrap_or_else(|err| {
+            eprintln!("{err}");
+            std::process::exit(2);
+        }),
+        None => Board::new(),
+    };
+
     let start = std::time::Instant::now();
-    dbg!(board.perft(0,3));
-    dbg!(start.elapsed());
+
+    let nodes = if divide {
+        let divided = board.perft_divide(depth, !pseudo);
+        for (mv, nodes) in &divided {
+            println!("{} {}", mv.to_uci(), nodes);
+        }
+        divided.iter().map(|(_, nodes)| nodes).sum()
+    } else {
+        count(&mut board, depth, pseudo)
+    };
+
+    let elapsed = start.elapsed();
+    println!();
+    println!("nodes {nodes}");
+    println!(
+        "time {} ms ({:.0} nps)",
+        elapsed.as_millis(),
+        nodes as f64 / elapsed.as_secs_f64().max(1e-9)
+    );
 }
@@ -1,37 +1,114 @@
-use queenfish::board::constants::IS_STOP;
 use queenfish::board::rook_magic::init_rook_magics;
-use queenfish::board::{Move, Turn};
+use queenfish::board::{
+    Board, Engine, GameStatus, Move, SearchConfig, SearchHandle, SearchLimits, SearchResult,
+};
 use queenfish::board::bishop_magic::init_bishop_magics;
-use queenfish::board::tt::TranspositionTable;
 use std::io::{self, Write};
-use std::sync::atomic::Ordering;
+use std::sync::mpsc::{self, RecvError, RecvTimeoutError};
 use std::thread;
+use std::time::Duration;
+
+const MAX_HASH_MB: usize = 65536;
+const MAX_THREADS: usize = 256;
+
+/// Builds the search limits from the arguments of a `go` command.
+fn parse_go(args: &[&str]) -> SearchLimits {
+    let mut limits = SearchLimits::default();
+
+    let value = |idx: usize| args.get(idx + 1).and_then(|value| value.parse::<u64>().ok());
+    let millis = |idx: usize| value(idx).map(Duration::from_millis);
+
+    for (idx, arg) in args.iter().enumerate() {
+        match *arg {
+            "depth" => limits.depth = value(idx).map(|depth| depth.min(i32::MAX as u64) as i32),
+            "nodes" => limits.nodes = value(idx),
+            "movetime" => limits.movetime = millis(idx),
+            "wtime" => limits.wtime = millis(idx),
+            "btime" => limits.btime = millis(idx),
+            "winc" => limits.winc = millis(idx),
+            "binc" => limits.binc = millis(idx),
+            "movestogo" => limits.movestogo = value(idx).map(|moves| moves as u32),
+            "mate" => limits.mate = value(idx).map(|moves| moves.min(u32::MAX as u64) as u32),
+            "infinite" => limits.infinite = true,
+            "ponder" => limits.ponder = true,
+            _ => (),
+        }
+    }
+
+    limits
+} //
+
+/// The number after `value` in a `setoption` command.
+fn option_value(input: &str) -> Option<usize> {
+    input
+        .split_whitespace()
+        .skip_while(|&token| token != "value")
+        .nth(1)
+        .and_then(|value| value.parse::<usize>().ok())
+} //
+
+fn report(result: SearchResult) {
+    match (result.best_move, result.ponder_move) {
+        (Some(best), Some(ponder)) => {
+            println!("bestmove {} ponder {}", best.to_uci(), ponder.to_uci())
+        }
+        (Some(best), None) => println!("bestmove {}", best.to_uci()),
+        (None, _) => println!("bestmove 0000"),
+    }
+    io::stdout().flush().unwrap();
+} //
+
+/// Stops the running search, if any, and reports its best move.
+fn finish_search(search: &mut Option<SearchHandle>) {
+    if let Some(search) = search.take() {
+        search.stop();
+        report(search.join());
+    }
+} //
 
 fn main() {
     init_bishop_magics();
     init_rook_magics();
 
-    let mut board = queenfish::board::Board::new();
-
-    let mut tt = TranspositionTable::new(20);
+    let mut board = Board::new();
 
-    let mut depth = 64;
-    let mut time = std::time::Duration::from_secs(30000);
+    let mut engine = Engine::new(SearchConfig::default());
+    let mut search: Option<SearchHandle> = None;
 
-    let mut use_tt = true;
-    let mut use_lmr = true;
-    let mut use_null_move = true;
-    let mut use_q = true;
-    let mut use_move_ordering = true;
+    // Commands are read on their own thread so that searches finishing on their
+    // own get reported while waiting for input
+    let (sender, commands) = mpsc::channel::<String>();
+    thread::spawn(move || {
+        for line in io::stdin().lines() {
+            let Ok(line) = line else { break };
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
 
     loop {
         io::stdout().flush().unwrap();
 
-        // Read Input
-        let mut input = String::new();
-        io::stdin()
-            .read_line(&mut input)
-            .expect("Failed to read prompt");
+        if search.as_ref().is_some_and(|search| !search.is_running()) {
+            report(search.take().unwrap().join());
+        }
+
+        // Read Input, only polling while a search may finish on its own
+        let input = match search {
+            None => match commands.recv() {
+                Ok(input) => input,
+                Err(RecvError) => break,
+            },
+            Some(_) => match commands.recv_timeout(Duration::from_millis(1)) {
+                Ok(input) => input,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => {
+                    finish_search(&mut search);
+                    break;
+                }
+            },
+        };
 
         let input = input.trim();
 
@@ -44,6 +121,17 @@ fn main() {
             println!("option name UseNullMove type check default true");
             println!("option name UseQuiesense type check default true");
             println!("option name UseMoveOrder type check default true");
+            println!("option name UsePVS type check default true");
+            println!("option name UseAspiration type check default true");
+            println!("option name UseLegalMoveGen type check default true");
+            println!("option name OwnBook type check default true");
+            println!("option name Ponder type check default false");
+            println!(
+                "option name Hash type spin default {} min 1 max {MAX_HASH_MB}",
+                Engine::DEFAULT_HASH_MB
+            );
+            println!("option name Clear Hash type button");
+            println!("option name Threads type spin default 1 min 1 max {MAX_THREADS}");
             println!("uciok");
             io::stdout().flush().unwrap();
         } else if input == "isready" {
@@ -51,8 +139,9 @@ fn main() {
             io::stdout().flush().unwrap();
         } else if input == "ucinewgame" {
             // println!("ok");
+            finish_search(&mut search);
             board.reset_to_default();
-            tt = TranspositionTable::new(20);
+            engine.new_game();
         } else if input.starts_with("position") {
             let tokens: Vec<&str> = input.split_whitespace().collect();
 
@@ -60,23 +149,38 @@ fn main() {
 
             let mut idx = 1;
 
-            if tokens[idx] == "startpos" {
+            if tokens.get(idx) == Some(&"startpos") {
                 idx += 1;
-            } else if tokens[idx] == "fen" {
-                if tokens.len() < idx + 6 {
-                    println!("Invalid FEN , Expected 6 fields , found {}", tokens.len() - idx);
-                    continue;
+            } else if tokens.get(idx) == Some(&"fen") {
+                let fen_end = tokens
+                    .iter()
+                    .position(|&token| token == "moves")
+                    .unwrap_or(tokens.len());
+                let fen = tokens[idx + 1..fen_end].join(" ");
+
+                match Board::from_fen(&fen) {
+                    Ok(parsed) => board = parsed,
+                    Err(err) => {
+                        println!("info string {err}");
+                        io::stdout().flush().unwrap();
+                        continue;
+                    }
                 }
-                let fen = tokens[idx + 1..idx + 7].join(" ");
-                board.load_from_fen(&fen);
-                idx += 7;
+                idx = fen_end;
             }
 
             if idx < tokens.len() && tokens[idx] == "moves" {
                 idx += 1;
                 while idx < tokens.len() {
-                    let mv = Move::from_uci(tokens[idx], &board);
-                    board.make_move(mv);
+                    match Move::parse_uci(tokens[idx], &board) {
+                        Ok(mv) => {
+                            board.make_move(mv);
+                        }
+                        Err(err) => {
+                            println!("info string {err}");
+                            break;
+                        }
+                    }
                     idx += 1;
                 }
             }
@@ -84,117 +188,75 @@ fn main() {
             // dbg!(board.to_fen());
             io::stdout().flush().unwrap();
         } else if input.starts_with("setoption") {
+            finish_search(&mut search);
+            let config = &mut engine.config;
             if input.contains("UseTT") {
-                use_tt = input.contains("true");
+                config.tt = input.contains("true");
             } else if input.contains("UseLMR") {
-                use_lmr = input.contains("true");
+                config.lmr = input.contains("true");
             } else if input.contains("UseNullMove") {
-                use_null_move = input.contains("true");
+                config.null_move_pruning = input.contains("true");
             } else if input.contains("UseQuiesense") {
-                use_q = input.contains("true");
+                config.quiescence = input.contains("true");
             } else if input.contains("UseMoveOrder") {
-                use_move_ordering = input.contains("true");
-            }
-        } else if input.starts_with("go") {
-            let args = input.split(' ').collect::<Vec<&str>>();
-
-            let depth_parsed = args
-                .iter()
-                .position(|&x| x == "depth")
-                .and_then(|i| args.get(i + 1));
-
-            let time_parsed = args
-                .iter()
-                .position(|&x| x == "movetime")
-                .and_then(|i| args.get(i + 1));
-
-            let w_time_parsed = args
-                .iter()
-                .position(|&x| x == "wtime")
-                .and_then(|i| args.get(i + 1));
-
-            let b_time_parsed = args
-                .iter()
-                .position(|&x| x == "btime")
-                .and_then(|i| args.get(i + 1));
-
-            if let Some(w_time_str) = w_time_parsed {
-                let white_time = w_time_str.parse::<u64>().unwrap_or_else(|_| {
-                    println!("Invalid time");
-                    time.as_millis() as u64 * 100
-                });
-                if board.turn == Turn::WHITE {
-                    time = std::time::Duration::from_millis(white_time / 100);
+                config.move_ordering = input.contains("true");
+            } else if input.contains("UsePVS") {
+                config.pvs = input.contains("true");
+            } else if input.contains("UseAspiration") {
+                config.aspiration_windows = input.contains("true");
+            } else if input.contains("UseLegalMoveGen") {
+                config.legal_move_gen = input.contains("true");
+            } else if input.contains("OwnBook") {
+                config.own_book = input.contains("true");
+            } else if input.contains("Clear Hash") {
+                engine.clear_hash();
+            } else if input.contains("Hash") {
+                match option_value(input) {
+                    Some(megabytes) => {
+                        if let Err(err) = engine.set_hash_size(megabytes.clamp(1, MAX_HASH_MB)) {
+                            println!("info string {err}");
+                        }
+                    }
+                    None => println!("info string invalid Hash value"),
                 }
-            }
-
-            if let Some(b_time_str) = b_time_parsed {
-                let black_time = b_time_str.parse::<u64>().unwrap_or_else(|_| {
-                    println!("Invalid time");
-                    time.as_millis() as u64 * 100
-                });
-                if board.turn == Turn::BLACK {
-                    time = std::time::Duration::from_millis(black_time / 100);
+            } else if input.contains("Threads") {
+                match option_value(input) {
+                    Some(threads) => {
+                        if let Err(err) = engine.set_threads(threads.clamp(1, MAX_THREADS)) {
+                            println!("info string {err}");
+                        }
+                    }
+                    None => println!("info string invalid Threads value"),
                 }
             }
+        } else if input.starts_with("go") {
+            finish_search(&mut search);
 
-            if let Some(depth_str) = depth_parsed {
-                depth = depth_str.parse::<i32>().unwrap_or_else(|_| {
-                    println!("Invalid depth");
-                    depth
-                });
-            }
+            let args = input.split_whitespace().collect::<Vec<&str>>();
+            let limits = parse_go(&args);
 
-            if let Some(time_str) = time_parsed {
-                time = std::time::Duration::from_millis(time_str.parse::<u64>().unwrap_or_else(|_| {
-                    println!("Invalid time");
-                    time.as_millis() as u64
-                }));
+            let status = board.game_status();
+            if status != GameStatus::Ongoing {
+                println!("info string {status}");
             }
-
-            IS_STOP.store(false, Ordering::Relaxed);
-
-            let mut board_clone = board.clone();
-            let mut tt_clone = tt.clone();
-
-            thread::spawn(move || {
-                let mv = board_clone.engine(
-                    depth,
-                    true,
-                    use_tt,
-                    use_null_move,
-                    use_lmr,
-                    use_q,
-                    use_move_ordering,
-                    time,
-                    Some(&mut tt_clone),
-                );
-
-                println!("bestmove {}", mv.to_uci());
+            if !status.has_legal_moves() {
+                println!("bestmove 0000");
                 io::stdout().flush().unwrap();
-            });
-
-            // println!(
-            //     "bestmove {}",
-            //     board
-            //         .engine(
-            //             depth,
-            //             true,
-            //             use_tt,
-            //             use_null_move,
-            //             use_lmr,
-            //             use_q,
-            //             use_move_ordering,
-            //             time,
-            //             Some(&mut tt),
-            //         )
-            //         .to_uci()
-            // );
-            io::stdout().flush().unwrap();
+                continue;
+            }
+
+            search = Some(engine.start_search(board.clone(), limits));
         } else if input == "quit" {
+            finish_search(&mut search);
             break;
-        } else if input == "stop" {
-            IS_STOP.store(true, std::sync::atomic::Ordering::Relaxed);
+        } else if input == "stop"
+            && let Some(search) = &search
+        {
+            search.stop();
+        } else if input == "ponderhit"
+            && let Some(search) = &search
+        {
+            search.ponderhit();
         }
     }
 }
@@ -1,8 +1,8 @@
 use crate::board::constants::{RANK_1, RANK_2, RANK_7, RANK_8};
-use crate::board::zobrist::{Z_PIECE, Z_SIDE , Z_CASTLING};
+use crate::board::zobrist::{Z_PIECE, Z_SIDE , Z_CASTLING, Z_EN_PASSANT};
 use crate::board::{BitBoard, BitBoards, Turn , Move};
 use crate::board::bishop_magic::bishop_attacks;
-use crate::board::constants::KNIGHTS_ATTACK_TABLE;
+use crate::board::constants::{BLACK_PAWN_ATTACKS, KNIGHTS_ATTACK_TABLE, WHITE_PAWN_ATTACKS};
 use crate::board::openings::OPENING_BOOK;
 use crate::board::rook_magic::rook_attacks;
 use crate::board::pieces::PieceType;
@@ -20,6 +20,8 @@ pub struct Board {
     pub castling: u8,
     pub history: Vec<u64>,
     pub last_irreversible_move: usize,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
     pub mat_eval: i32,      // Always white favor
     pub mg_pst_eval: i32,   // Always white favor
     pub eg_pst_eval: i32,   // Always white favor
@@ -44,6 +46,8 @@ impl Board {
             mobility_eval: 0,
             history: Vec::new(),
             last_irreversible_move: 0,
+            halfmove_clock: 0,
+            fullmove_number: 1,
             number_of_pieces: 32,
             number_of_pawns: 16,
         };
@@ -73,6 +77,9 @@ impl Board {
         self.mobility_eval = 0;
         self.number_of_pieces = 32;
         self.number_of_pawns = 16;
+        self.last_irreversible_move = 0;
+        self.halfmove_clock = 0;
+        self.fullmove_number = 1;
         self.history = vec![self.hash];
     } //
     pub fn reset_to_zero(&mut self) {
@@ -89,6 +96,9 @@ impl Board {
         self.mobility_eval = 0;
         self.number_of_pawns = 0;
         self.number_of_pieces = 0;
+        self.last_irreversible_move = 0;
+        self.halfmove_clock = 0;
+        self.fullmove_number = 1;
         self.history = vec![self.hash];
     } //
     pub fn get_all_white_bits(&self) -> BitBoard {
@@ -170,6 +180,7 @@ impl Board {
             }
             // // count mobility
             self.mobility_eval += piece.mobility_score(sq, self.occupied.0);
+            self.mobility_eval += self.sliders_mobility_change(sq, self.occupied.0 & !mask);
             self.mg_pst_eval += piece.pst(sq, false);
             self.eg_pst_eval += piece.pst(sq, true);
 
@@ -191,6 +202,7 @@ impl Board {
             }
             // // count mobility
             self.mobility_eval -= piece.mobility_score(sq, self.occupied.0);
+            self.mobility_eval += self.sliders_mobility_change(sq, self.occupied.0 | mask);
             self.mg_pst_eval -= piece.pst(sq, false);
             self.eg_pst_eval -= piece.pst(sq, true);
 
@@ -198,104 +210,33 @@ impl Board {
         }
     } //
 
-    pub fn load_from_fen(&mut self, fen: &str) {
-        self.reset_to_zero();
-
-        // let (position, turn) = fen.split_once(' ').unwrap();
-
-        let splitted = fen.split_ascii_whitespace().collect::<Vec<_>>();
-
-        if splitted.len() < 2 {
-            panic!("Invalid FEN");
-        }
-
-        let position = splitted[0];
-        let turn = splitted[1];
-
-        if let Some(castling) = splitted.get(2) {
-            if castling != &"-" {
-                if castling.contains('K') {
-                    self.castling |= 0b0001;
-                }
-                if castling.contains('Q') {
-                    self.castling |= 0b0010;
-                }
-                if castling.contains('k') {
-                    self.castling |= 0b0100;
-                }
-                if castling.contains('q') {
-                    self.castling |= 0b1000;
-                }
-            }
-
-            if let Some(en_passant) = splitted.get(3) {
-                if en_passant != &"-" {
-                    let bytes = en_passant.as_bytes();
-                    let file = (bytes[0] - b'a') as usize; // 'a'..'h' -> 0..7
-                    let rank = (bytes[1] - b'1') as usize; // '1'..'8' -> 0..7
-
-                    if rank == 2 || rank == 5 {
-                        self.en_passant = Some(rank * 8 + file);
-                    } else {
-                        self.en_passant = None; // invalid FEN safety
-                    }
-                }
+    /// Mobility change of the sliders whose rays reach `sq` now that it went from
+    /// `before` to the current occupancy: they now stop there or see past it.
+    fn sliders_mobility_change(&self, sq: usize, before: u64) -> i32 {
+        let bbs = &self.bitboards;
+        let diagonal = bbs[2].0 | bbs[4].0 | bbs[8].0 | bbs[10].0;
+        let straight = bbs[3].0 | bbs[4].0 | bbs[9].0 | bbs[10].0;
+        let after = self.occupied.0;
+
+        let mut sliders =
+            (bishop_attacks(sq, after) & diagonal) | (rook_attacks(sq, after) & straight);
+        let mut change = 0;
+        while sliders != 0 {
+            let slider = sliders.trailing_zeros() as usize;
+            sliders &= sliders - 1;
+
+            if let Some(piece) = self.piece_at[slider] {
+                change += piece.mobility_score(slider, after) - piece.mobility_score(slider, before);
             }
         }
+        change
+    } //
 
-        self.turn = match turn {
-            "w" => Turn::WHITE,
-            "b" => Turn::BLACK,
-            _ => panic!("Invalid side to move"),
-        };
-
-        let rows: Vec<&str> = position.split('/').collect();
-
-        for rank in 0..8 {
-            let mut file: usize = 0;
-            for char in rows[rank].chars() {
-                if let Some(number) = char.to_digit(10) {
-                    file += number as usize;
-                } else {
-                    let square_index = (7 - rank) * 8 + file;
-                    let bit = 1u64 << square_index;
-                    let target_board = match char {
-                        'P' => Some(&mut self.bitboards[PieceType::WhitePawn.piece_index()]),
-                        'R' => Some(&mut self.bitboards[PieceType::WhiteRook.piece_index()]),
-                        'Q' => Some(&mut self.bitboards[PieceType::WhiteQueen.piece_index()]),
-                        'K' => Some(&mut self.bitboards[PieceType::WhiteKing.piece_index()]),
-                        'N' => Some(&mut self.bitboards[PieceType::WhiteKnight.piece_index()]),
-                        'B' => Some(&mut self.bitboards[PieceType::WhiteBishop.piece_index()]),
-                        'p' => Some(&mut self.bitboards[PieceType::BlackPawn.piece_index()]),
-                        'r' => Some(&mut self.bitboards[PieceType::BlackRook.piece_index()]),
-                        'q' => Some(&mut self.bitboards[PieceType::BlackQueen.piece_index()]),
-                        'k' => Some(&mut self.bitboards[PieceType::BlackKing.piece_index()]),
-                        'n' => Some(&mut self.bitboards[PieceType::BlackKnight.piece_index()]),
-                        'b' => Some(&mut self.bitboards[PieceType::BlackBishop.piece_index()]),
-                        _ => None,
-                    };
-
-                    if let Some(bitboard) = target_board {
-                        bitboard.0 = bit | bitboard.0;
-                        file += 1;
-                    }
-                }
-            }
+    pub fn load_from_fen(&mut self, fen: &str) {
+        match Board::from_fen(fen) {
+            Ok(board) => *self = board,
+            Err(err) => panic!("{err}"),
         }
-
-        self.occupied = self.get_all_bits();
-        self.piece_at = self.generate_piece_at();
-        self.hash = self.compute_hash();
-
-        self.mat_eval = self.pieces_score();
-        self.mobility_eval = self.generate_mobility_eval();
-        let (mg_score, eg_score) = self.generate_pst_score();
-        self.mg_pst_eval = mg_score;
-        self.eg_pst_eval = eg_score;
-
-        self.history = vec![self.hash];
-        self.number_of_pieces = self.generate_pieces_count() as usize;
-        self.number_of_pawns = self.generate_pawns_count() as usize;
     } //
 
     pub fn to_fen(&self) -> String {
@@ -388,6 +329,8 @@ impl Board {
             fen.push('-');
         }
 
+        fen.push_str(&format!(" {} {}", self.halfmove_clock, self.fullmove_number));
+
         fen
     } //
 
@@ -422,20 +365,16 @@ impl Board {
     } //
 
     pub fn is_3fold_repetition(&self) -> bool {
-        let hash = self.hash;
-
-        let mut count = 0;
-        let len = self.history.len();
+        self.repetition_count() >= 3
+    } //
 
-        for i in (self.last_irreversible_move..len).rev() {
-            if self.history[i] == hash {
-                count += 1;
-                if count == 3 {
-                    return true;
-                }
-            }
+    pub fn is_fifty_move_draw(&mut self) -> bool {
+        if self.halfmove_clock < 100 {
+            return false;
         }
-        return false;
+
+        // A mate delivered on the hundredth halfmove still stands
+        !self.is_king_in_check(self.turn) || !self.generate_moves().is_empty()
     } //
 
     pub fn print_board(&self) {
@@ -545,16 +484,16 @@ impl Board {
             if let Some(piece) = piece {
                 match piece {
                     PieceType::BlackBishop => {
-                        eval += 2 * bishop_attacks(sq, self.occupied.0).count_ones() as i32;
+                        eval -= 2 * bishop_attacks(sq, self.occupied.0).count_ones() as i32;
                     }
                     PieceType::WhiteBishop => {
-                        eval -= 2 * bishop_attacks(sq, self.occupied.0).count_ones() as i32;
+                        eval += 2 * bishop_attacks(sq, self.occupied.0).count_ones() as i32;
                     }
                     PieceType::BlackRook => {
-                        eval += 2 * rook_attacks(sq, self.occupied.0).count_ones() as i32;
+                        eval -= 2 * rook_attacks(sq, self.occupied.0).count_ones() as i32;
                     }
                     PieceType::WhiteRook => {
-                        eval -= 2 * rook_attacks(sq, self.occupied.0).count_ones() as i32;
+                        eval += 2 * rook_attacks(sq, self.occupied.0).count_ones() as i32;
                     }
                     PieceType::BlackKnight => {
                         eval -= 2 * KNIGHTS_ATTACK_TABLE[sq].count_ones() as i32;
@@ -621,6 +560,35 @@ impl Board {
             h ^= Z_CASTLING[3];
         }
 
+        h ^= self.en_passant_key();
+
         h
     } //
+
+    /// Zobrist key of the en passant file, or 0 when no pawn of the side to move
+    /// can actually capture on the en passant square (Polyglot convention).
+    #[inline(always)]
+    pub fn en_passant_key(&self) -> u64 {
+        let Some(en_passant) = self.en_passant else {
+            return 0;
+        };
+
+        // Squares a pawn of the side to move would capture from
+        let (pawns, attackers_mask) = match self.turn {
+            Turn::WHITE => (
+                self.bitboards[PieceType::WhitePawn.piece_index()].0,
+                BLACK_PAWN_ATTACKS[en_passant],
+            ),
+            Turn::BLACK => (
+                self.bitboards[PieceType::BlackPawn.piece_index()].0,
+                WHITE_PAWN_ATTACKS[en_passant],
+            ),
+        };
+
+        if pawns & attackers_mask != 0 {
+            Z_EN_PASSANT[en_passant % 8]
+        } else {
+            0
+        }
+    } //
 } //
@@ -1,31 +1,35 @@
 use super::constants::MVV_LVA;
-use crate::board::constants::IS_STOP;
-use crate::board::tt::{Bound, TranspositionTable};
+use crate::board::history::{History, PreviousMoves};
+use crate::board::move_picker::MovePicker;
+use crate::board::search::{MATE_BOUND, MATE_SCORE};
+use crate::board::search::{SearchConfig, SearchHandle, SearchLimits, SearchResult};
+use crate::board::tt::{Bound, TranspositionTable, score_to_tt};
 use crate::board::{Board, Move, Turn};
 use smallvec::SmallVec;
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::Duration;
-
-static NODE_COUNT: AtomicU64 = AtomicU64::new(0);
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError, mpsc};
+use std::thread;
+use std::time::{Duration, Instant};
 
 impl Board {
     #[inline(always)]
     pub fn pieces_score(&self) -> i32 {
         let bbs = &self.bitboards;
 
-        let white = bbs[0].0.count_ones() * 100   // pawn
-        + bbs[1].0.count_ones() * 300   // knight
-        + bbs[2].0.count_ones() * 300   // bishop
-        + bbs[3].0.count_ones() * 500   // rook
-        + bbs[4].0.count_ones() * 900; // queen
+        let white = bbs[0].0.count_ones() as i32 * 100   // pawn
+        + bbs[1].0.count_ones() as i32 * 300   // knight
+        + bbs[2].0.count_ones() as i32 * 300   // bishop
+        + bbs[3].0.count_ones() as i32 * 500   // rook
+        + bbs[4].0.count_ones() as i32 * 900; // queen
 
-        let black = bbs[6].0.count_ones() * 100
-            + bbs[7].0.count_ones() * 300
-            + bbs[8].0.count_ones() * 300
-            + bbs[9].0.count_ones() * 500
-            + bbs[10].0.count_ones() * 900;
+        let black = bbs[6].0.count_ones() as i32 * 100
+            + bbs[7].0.count_ones() as i32 * 300
+            + bbs[8].0.count_ones() as i32 * 300
+            + bbs[9].0.count_ones() as i32 * 500
+            + bbs[10].0.count_ones() as i32 * 900;
 
-        (white - black) as i32
+        white - black
     } //
 
     pub fn evaluate(&mut self) -> i32 {
@@ -60,50 +64,470 @@ impl Board {
         MVV_LVA[victim.piece_index() % 6][attacker.piece_index() % 6]
     } //
 
-    pub fn score_move(
-        &self,
-        mv: Move,
-        ply: usize,
-        killer: &[[Option<Move>; 2]],
-        tt_move: Option<Move>,
-    ) -> i32 {
-        if Some(mv) == tt_move {
-            10_000
-        } else if Some(mv) == killer[ply][0] {
-            9_000
-        } else if Some(mv) == killer[ply][1] {
-            8_000
-        } else if mv.is_capture() {
-            10_000 + self.mvv_lva(mv)
-        } else {
-            0
+    pub fn perft(&mut self, depth: i32, max_depth: i32) -> i64 {
+        if depth == max_depth {
+            return 1;
+        }
+        let mut moves = SmallVec::new();
+        self.generate_pesudo_moves(&mut moves);
+
+        let mut nodes = 0;
+
+        let current_turn = self.turn; // Will be the opposite one making the move
+
+        for mv in moves {
+            if mv.is_castling() && self.is_castling_through_check(mv) {
+                continue;
+            }
+
+            // Turn switches here
+            let unmake = self.make_move(mv);
+
+            if self.is_king_in_check(current_turn) {
+                self.unmake_move(unmake);
+                continue;
+            }
+
+            let inner_nodes = self.perft(depth + 1, max_depth);
+
+            // Turn switches back
+            self.unmake_move(unmake);
+
+            nodes += inner_nodes;
         }
+
+        nodes
     } //
 
-    pub fn sort_moves_by_score(
-        &mut self,
-        moves: &mut SmallVec<[Move; 256]>,
-        ply: usize,
-        killer: &[[Option<Move>; 2]],
-        tt_move: Option<Move>,
-    ) {
-        moves.sort_unstable_by(|a, b| {
-            let va = self.score_move(*a, ply, killer, tt_move);
-            let vb = self.score_move(*b, ply, killer, tt_move);
-            vb.cmp(&va)
+    /// Leaf node count below every legal root move, counted with the legal move
+    /// generator when `legal_move_gen` is set and with `perft` otherwise.
+    pub fn perft_divide(&mut self, depth: i32, legal_move_gen: bool) -> Vec<(Move, i64)> {
+        if depth <= 0 {
+            return Vec::new();
+        }
+        let moves = self.generate_moves();
+
+        moves
+            .iter()
+            .map(|mv| {
+                let unmake = self.make_move(*mv);
+                let nodes = match legal_move_gen {
+                    true => self.perft_legal(depth - 1),
+                    false => self.perft(1, depth),
+                };
+                self.unmake_move(unmake);
+                (*mv, nodes)
+            })
+            .collect()
+    } //
+} //
+
+/// Returned by the `Engine` settings that can't change while a search is running.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SearchRunning;
+
+impl fmt::Display for SearchRunning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a search is running, stop it first")
+    }
+} //
+
+impl std::error::Error for SearchRunning {}
+
+/// The searcher, unless a search holds it.
+fn idle(searcher: &Mutex<Searcher>) -> Result<MutexGuard<'_, Searcher>, SearchRunning> {
+    match searcher.try_lock() {
+        Ok(searcher) => Ok(searcher),
+        Err(TryLockError::WouldBlock) => Err(SearchRunning),
+        Err(TryLockError::Poisoned(err)) => panic!("{err}"),
+    }
+} //
+
+/// Stops the search of the `Engine` it was taken from, can be sent to other threads.
+#[derive(Debug, Clone, Default)]
+pub struct StopHandle(Arc<AtomicBool>);
+
+impl StopHandle {
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    } //
+
+    pub fn is_stopped(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    } //
+
+    fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    } //
+} //
+
+/// A search instance, owns everything a search mutates so several engines can
+/// run side by side in one process.
+pub struct Engine {
+    pub config: SearchConfig,
+    /// Kept across searches, shared with the thread running the search
+    tt: Arc<TranspositionTable>,
+    searcher: Arc<Mutex<Searcher>>,
+    stop: StopHandle,
+    pondering: Arc<AtomicBool>,
+} //
+
+impl Engine {
+    pub const DEFAULT_HASH_MB: usize = 16;
+
+    pub fn new(config: SearchConfig) -> Engine {
+        let tt = Arc::new(TranspositionTable::new(Self::DEFAULT_HASH_MB));
+        let stop = StopHandle::default();
+        let pondering = Arc::new(AtomicBool::new(false));
+        let node_counts = Arc::new(vec![NodeCount::default()]);
+
+        Engine {
+            config,
+            tt: Arc::clone(&tt),
+            searcher: Arc::new(Mutex::new(Searcher::new(
+                0,
+                tt,
+                node_counts,
+                stop.clone(),
+                Arc::clone(&pondering),
+            ))),
+            stop,
+            pondering,
+        }
+    } //
+
+    pub fn stop_handle(&self) -> StopHandle {
+        self.stop.clone()
+    } //
+
+    /// Nodes visited by the last search on all threads, waits for a running search
+    /// to finish.
+    pub fn nodes(&self) -> u64 {
+        self.searcher.lock().unwrap().total_nodes()
+    } //
+
+    /// Nodes visited by the last search on every thread, the main thread first.
+    pub fn thread_nodes(&self) -> Vec<u64> {
+        let searcher = self.searcher.lock().unwrap();
+        searcher
+            .node_counts
+            .iter()
+            .map(|count| count.0.load(Ordering::Relaxed))
+            .collect()
+    } //
+
+    /// Forgets everything learned from previous searches.
+    pub fn new_game(&mut self) {
+        self.tt.clear();
+
+        let mut searcher = self.searcher.lock().unwrap();
+        searcher.new_game();
+        for helper in &searcher.helpers {
+            helper.lock().unwrap().new_game();
+        }
+    } //
+
+    /// Searches with `threads` threads sharing the transposition table, refused
+    /// while a search is running.
+    pub fn set_threads(&mut self, threads: usize) -> Result<(), SearchRunning> {
+        let mut searcher = idle(&self.searcher)?;
+        let threads = threads.max(1);
+        let node_counts: Arc<Vec<NodeCount>> =
+            Arc::new((0..threads).map(|_| NodeCount::default()).collect());
+
+        searcher.node_counts = Arc::clone(&node_counts);
+        searcher.helpers = (1..threads)
+            .map(|id| {
+                Arc::new(Mutex::new(Searcher::new(
+                    id,
+                    Arc::clone(&self.tt),
+                    Arc::clone(&node_counts),
+                    searcher.helper_stop.clone(),
+                    Arc::clone(&self.pondering),
+                )))
+            })
+            .collect();
+        Ok(())
+    } //
+
+    /// Empties the transposition table, does not wait for a running search.
+    pub fn clear_hash(&self) {
+        self.tt.clear();
+    } //
+
+    /// Replaces the transposition table with an empty one of `megabytes`, refused
+    /// while a search is running.
+    pub fn set_hash_size(&mut self, megabytes: usize) -> Result<(), SearchRunning> {
+        let mut searcher = idle(&self.searcher)?;
+
+        // Free the old table before allocating the new one
+        for megabytes in [0, megabytes] {
+            self.tt = Arc::new(TranspositionTable::new(megabytes));
+            searcher.tt = Arc::clone(&self.tt);
+            for helper in &searcher.helpers {
+                helper.lock().unwrap().tt = Arc::clone(&self.tt);
+            }
+        }
+        Ok(())
+    } //
+
+    /// Searches `board` on a new thread. A search still running is stopped first,
+    /// its handle returns the best move it found so far.
+    pub fn start_search(&self, board: Board, limits: SearchLimits) -> SearchHandle {
+        self.stop.stop();
+        drop(self.searcher.lock().unwrap());
+
+        self.stop.reset();
+        self.pondering.store(limits.ponder, Ordering::Relaxed);
+        self.tt.new_search();
+
+        let searcher = Arc::clone(&self.searcher);
+        let config = self.config;
+        let (started, has_started) = mpsc::channel();
+
+        let thread = thread::spawn(move || {
+            let mut board = board;
+            let mut searcher = searcher.lock().unwrap();
+            let _ = started.send(());
+            searcher.run(&mut board, &limits, &config)
         });
+
+        // The searcher stays locked until the search is over, so the next call
+        // can't slip in before this search started
+        has_started.recv().expect("search thread panicked");
+
+        SearchHandle::new(thread, self.stop.clone(), Arc::clone(&self.pondering))
+    } //
+
+    /// Blocking search, same as joining `start_search`.
+    pub fn search(&mut self, board: &Board, limits: &SearchLimits) -> SearchResult {
+        self.start_search(board.clone(), *limits).join()
+    } //
+} //
+
+/// Nodes searched by one thread, padded so that threads never write to the same
+/// cache line.
+#[repr(align(64))]
+#[derive(Default)]
+struct NodeCount(AtomicU64);
+
+/// Everything a search mutates, locked by the thread running the search.
+struct Searcher {
+    /// 0 for the main thread, which reports the search, helpers count up from 1
+    id: usize,
+    tt: Arc<TranspositionTable>,
+    killer_moves: [[Option<Move>; 2]; Searcher::MAX_PLY],
+    history: History,
+    /// Move played at every ply of the current line, `None` for null moves
+    move_stack: [Option<Move>; Searcher::MAX_PLY],
+    /// Triangular PV table, `pv_table[ply]` is the best line found from `ply` on
+    pv_table: Vec<Vec<Move>>,
+    nodes: u64,
+    /// Nodes of all threads together
+    node_limit: u64,
+    deadline: Option<Instant>,
+    timed_out: bool,
+    /// Set once all threads together went past `node_limit`
+    out_of_nodes: bool,
+    stop: StopHandle,
+    pondering: Arc<AtomicBool>,
+    /// `nodes` of every thread, published for the main thread's reports
+    node_counts: Arc<Vec<NodeCount>>,
+    /// Lazy SMP helpers of the main thread, they search the same position and
+    /// only share the transposition table
+    helpers: Vec<Arc<Mutex<Searcher>>>,
+    /// Stops the helpers once the main thread is done
+    helper_stop: StopHandle,
+} //
+
+impl Searcher {
+    /// Time and the nodes of the other threads are only checked every few
+    /// thousand nodes.
+    const LIMIT_CHECK_INTERVAL: u64 = 2048;
+    const MAX_PLY: usize = 128;
+
+    fn new(
+        id: usize,
+        tt: Arc<TranspositionTable>,
+        node_counts: Arc<Vec<NodeCount>>,
+        stop: StopHandle,
+        pondering: Arc<AtomicBool>,
+    ) -> Searcher {
+        // A ply past the deepest iteration would index past the per-ply tables
+        debug_assert!((SearchLimits::MAX_DEPTH as usize) < Searcher::MAX_PLY);
+
+        Searcher {
+            id,
+            tt,
+            killer_moves: [[None; 2]; Searcher::MAX_PLY],
+            history: History::default(),
+            move_stack: [None; Searcher::MAX_PLY],
+            pv_table: vec![Vec::new(); Searcher::MAX_PLY],
+            nodes: 0,
+            node_limit: u64::MAX,
+            deadline: None,
+            timed_out: false,
+            out_of_nodes: false,
+            stop,
+            pondering,
+            node_counts,
+            helpers: vec![],
+            helper_stop: StopHandle::default(),
+        }
+    } //
+
+    fn new_game(&mut self) {
+        self.killer_moves = [[None; 2]; Searcher::MAX_PLY];
+        self.history.clear();
+    } //
+
+    /// The two moves leading to `ply`.
+    fn previous_moves(&self, ply: usize) -> PreviousMoves {
+        [
+            ply.checked_sub(1).and_then(|ply| self.move_stack[ply]),
+            ply.checked_sub(2).and_then(|ply| self.move_stack[ply]),
+        ]
+    } //
+
+    fn total_nodes(&self) -> u64 {
+        self.node_counts
+            .iter()
+            .map(|count| count.0.load(Ordering::Relaxed))
+            .sum()
+    } //
+
+    fn publish_nodes(&self) {
+        self.node_counts[self.id].0.store(self.nodes, Ordering::Relaxed);
+    } //
+
+    fn should_stop(&mut self) -> bool {
+        self.publish_nodes();
+
+        if self.nodes.is_multiple_of(Self::LIMIT_CHECK_INTERVAL) {
+            self.timed_out |= self.is_out_of_time();
+            self.out_of_nodes |= self.total_nodes() > self.node_limit;
+        }
+
+        self.is_interrupted()
+    } //
+
+    fn is_interrupted(&self) -> bool {
+        self.timed_out
+            || self.out_of_nodes
+            || self.nodes > self.node_limit
+            || self.stop.is_stopped()
+    } //
+
+    fn is_out_of_time(&self) -> bool {
+        !self.pondering.load(Ordering::Relaxed)
+            && self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
     } //
 
-    pub fn quiescence(&mut self, alpha: i32, beta: i32) -> i32 {
-        NODE_COUNT.fetch_add(1, Ordering::Relaxed);
-        let stand_pat = match self.turn {
-            Turn::WHITE => self.evaluate(),
-            Turn::BLACK => -self.evaluate(),
+    fn run(&mut self, board: &mut Board, limits: &SearchLimits, config: &SearchConfig) -> SearchResult {
+        let book_move = match config.own_book {
+            true => board.probe_opening(),
+            false => None,
+        };
+
+        let result = match book_move {
+            Some(best_move) => SearchResult {
+                best_move: Some(best_move),
+                ponder_move: None,
+                score: 0,
+                depth: 0,
+                pv: vec![best_move],
+                nodes: 0,
+            },
+            None => self.lazy_smp(board, limits, config),
         };
 
-        let margin = 900; // queen value
+        // The GUI expects no best move before it stops an infinite or pondering search
+        while (limits.infinite || self.pondering.load(Ordering::Relaxed))
+            && !self.stop.is_stopped()
+        {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        result
+    } //
+
+    /// Runs the helpers next to the main thread's search and keeps the result of
+    /// the deepest completed iteration.
+    fn lazy_smp(
+        &mut self,
+        board: &mut Board,
+        limits: &SearchLimits,
+        config: &SearchConfig,
+    ) -> SearchResult {
+        let start_time = Instant::now();
+        let helpers = self.helpers.clone();
+
+        for count in self.node_counts.iter() {
+            count.0.store(0, Ordering::Relaxed);
+        }
+        self.helper_stop.reset();
+
+        thread::scope(|scope| {
+            let threads: Vec<_> = helpers
+                .iter()
+                .map(|helper| {
+                    let mut board = board.clone();
+                    scope.spawn(move || {
+                        helper
+                            .lock()
+                            .unwrap()
+                            .iterative_deepening(&mut board, limits, config)
+                    })
+                })
+                .collect();
+
+            let mut result = self.iterative_deepening(board, limits, config);
+            self.helper_stop.stop();
+
+            for thread in threads {
+                let helper_result = thread.join().expect("search helper panicked");
+
+                if helper_result.depth > result.depth
+                    || (helper_result.depth == result.depth && helper_result.score > result.score)
+                {
+                    result = helper_result;
+                    if config.print_info {
+                        self.print_info(&result, start_time);
+                    }
+                }
+            }
+
+            result.nodes = self.total_nodes();
+            result
+        })
+    } //
+
+    fn print_info(&self, result: &SearchResult, start_time: Instant) {
+        println!(
+            "info depth {} score cp {} nodes {} time {} hashfull {} pv {}",
+            result.depth,
+            result.score,
+            self.total_nodes(),
+            start_time.elapsed().as_millis(),
+            self.tt.hashfull(),
+            result.pv.iter().map(|mv| mv.to_uci()).collect::<Vec<_>>().join(" ")
+        );
+    } //
+
+    pub fn quiescence(
+        &mut self,
+        board: &mut Board,
+        alpha: i32,
+        beta: i32,
+        config: &SearchConfig,
+    ) -> i32 {
+        self.nodes += 1;
+        let stand_pat = match board.turn {
+            Turn::WHITE => board.evaluate(),
+            Turn::BLACK => -board.evaluate(),
+        };
 
-        if stand_pat + margin < alpha {
+        if stand_pat + config.quiescence_margin < alpha {
             return alpha;
         }
 
@@ -112,23 +536,22 @@ impl Board {
         }
         let mut alpha = alpha.max(stand_pat);
 
-        let mut moves = SmallVec::new();
-        self.generate_pesudo_moves(&mut moves);
+        // Losing captures can't raise alpha above the stand pat score
+        let legality = config.legal_move_gen.then(|| board.legality());
+        let mut picker = MovePicker::quiescence(config.see_pruning, legality);
 
-        let iter = moves.iter().filter(|mv| mv.is_capture());
-
-        for mv in iter {
-            let undo = self.make_move(*mv);
+        while let Some(mv) = picker.next(board, &self.history, &[None; 2]) {
+            let undo = board.make_move(mv);
 
             // after make_move, side-to-move is the opponent
             // ensure the player who just moved is not in check
-            if self.is_king_in_check(self.opposite_turn()) {
-                self.unmake_move(undo);
+            if legality.is_none() && board.is_king_in_check(board.opposite_turn()) {
+                board.unmake_move(undo);
                 continue;
             }
 
-            let score = -self.quiescence(-beta, -alpha);
-            self.unmake_move(undo);
+            let score = -self.quiescence(board, -beta, -alpha, config);
+            board.unmake_move(undo);
 
             if score >= beta {
                 return beta;
@@ -141,467 +564,455 @@ impl Board {
 
     pub fn alpha_beta(
         &mut self,
+        board: &mut Board,
         ply: usize,
         remaining_depth: i8,
         mut alpha: i32,
         beta: i32,
-        tt: &mut TranspositionTable,
-        is_alpha_beta: bool,
-        is_tt: bool,
-        is_null_move_pruning: bool,
-        is_lmr: bool,
-        is_quiesense: bool,
-        killer_moves: &mut [[Option<Move>; 2]; 128],
+        config: &SearchConfig,
     ) -> i32 {
-        NODE_COUNT.fetch_add(1, Ordering::Relaxed);
-        if IS_STOP.load(Ordering::Relaxed) {
+        self.nodes += 1;
+        self.pv_table[ply].clear();
+
+        if self.should_stop() {
             return alpha;
         }
 
-        if self.is_3fold_repetition() {
+        if board.is_draw() {
             return 0;
         }
 
         let orig_alpha = alpha;
         let orig_beta = beta;
-        let mut best_move_from_tt: Option<Move> = None;
+        let mut best_move_from_tt: Option<u16> = None;
 
         // 1. TT LOOKUP
-        if is_tt {
-            if let Some(entry) = tt.probe(self.hash) {
+        if config.tt {
+            if let Some(entry) = self.tt.probe(board.hash) {
                 best_move_from_tt = Some(entry.best_move);
 
-                if entry.depth >= remaining_depth {
-                    match entry.bound {
-                        Bound::Exact => {
-                            let score = if entry.score.abs() > 29_000 {
-                                entry.score - (ply as i32)
-                            } else {
-                                entry.score
-                            };
-                            return score;
-                        }
-                        _ => (),
-                    }
+                if let Some(score) = entry.cutoff(remaining_depth, alpha, beta, ply) {
+                    return score;
                 }
             }
         };
 
         // 2. BASE CASE (Optimized)
         if remaining_depth == 0 {
-            if is_quiesense {
-                return self.quiescence(alpha, beta);
+            if config.quiescence {
+                return self.quiescence(board, alpha, beta, config);
             }
-            match self.turn {
-                Turn::BLACK => return -self.evaluate(),
-                Turn::WHITE => return self.evaluate(),
+            match board.turn {
+                Turn::BLACK => return -board.evaluate(),
+                Turn::WHITE => return board.evaluate(),
             }
         };
 
+        // Without it every move is made first and dropped when it leaves the king in check
+        let legality = config.legal_move_gen.then(|| board.legality());
+        let in_check = match legality {
+            Some(legality) => legality.in_check(),
+            None => board.is_king_in_check(board.turn),
+        };
+
         // 3. NULL MOVE PRUNING
-        if remaining_depth >= 3 && !self.is_king_in_check(self.turn) && is_null_move_pruning {
-            let r = 2;
-            self.switch_turn();
+        if remaining_depth >= config.null_move_min_depth
+            && !in_check
+            && config.null_move_pruning
+        {
+            let r = config.null_move_reduction;
+            let null_move_config = SearchConfig {
+                tt: false,
+                null_move_pruning: false,
+                lmr: false,
+                quiescence: false,
+                ..*config
+            };
+            let en_passant = board.make_null_move();
+            self.move_stack[ply] = None;
             let score = -self.alpha_beta(
+                board,
                 ply + 1,
-                remaining_depth - r - 1,
+                (remaining_depth - r - 1).max(0),
                 -beta,
                 -(beta - 1),
-                tt,
-                is_alpha_beta,
-                false,
-                false,
-                false,
-                false,
-                killer_moves,
+                &null_move_config,
             );
-            self.switch_turn();
+            board.unmake_null_move(en_passant);
             if score >= beta {
                 return beta;
             }
         };
 
         // 4. MOVE GENERATION (Only for internal nodes)
-        let mut moves = SmallVec::new();
-        self.generate_pesudo_moves(&mut moves);
+        // The table only keeps a compact move, find the full one
+        let tt_move = best_move_from_tt.and_then(|compact| board.move_from_compact(compact));
 
-        self.sort_moves_by_score(&mut moves, ply, killer_moves, best_move_from_tt);
+        let previous = self.previous_moves(ply);
+        let countermove = self.history.countermove(previous[0]);
 
-        let iter = moves.iter();
+        let mut picker = MovePicker::new(tt_move, self.killer_moves[ply], countermove, legality);
 
-        let mut found_legal = false;
+        let mut legal_moves = 0;
         let mut all_searched = true;
+        let mut failed_quiets: SmallVec<[Move; 64]> = SmallVec::new();
 
         let mut best_score = -30_000;
-        let mut best_move = moves[0];
-
-        let opposite_turn = self.opposite_turn();
+        let mut best_move = None;
 
         let remaining_depth_next = remaining_depth - 1;
 
-        for (index, mv) in iter.enumerate() {
-            if mv.is_castling() {
-                match mv.to() {
-                    6 => {
-                        if self.is_square_attacked(6, opposite_turn) {
-                            continue;
-                        } else if self.is_square_attacked(5, opposite_turn) {
-                            continue;
-                        } else if self.is_square_attacked(4, opposite_turn) {
-                            continue;
-                        }
-                    }
-                    2 => {
-                        if self.is_square_attacked(2, opposite_turn) {
-                            continue;
-                        } else if self.is_square_attacked(3, opposite_turn) {
-                            continue;
-                        } else if self.is_square_attacked(4, opposite_turn) {
-                            continue;
-                        }
-                    }
-                    58 => {
-                        if self.is_square_attacked(58, opposite_turn) {
-                            continue;
-                        } else if self.is_square_attacked(59, opposite_turn) {
-                            continue;
-                        } else if self.is_square_attacked(60, opposite_turn) {
-                            continue;
-                        }
-                    }
-                    62 => {
-                        if self.is_square_attacked(62, opposite_turn) {
-                            continue;
-                        } else if self.is_square_attacked(61, opposite_turn) {
-                            continue;
-                        } else if self.is_square_attacked(60, opposite_turn) {
-                            continue;
-                        }
-                    }
-                    _ => (),
-                }
-            };
+        let mut move_count = 0;
+        while let Some(mv) = picker.next(board, &self.history, &previous) {
+            move_count += 1;
+
+            // Close to the horizon, skip quiet moves that hang material
+            if config.see_pruning
+                && remaining_depth <= config.see_quiet_max_depth
+                && legal_moves > 0
+                && !in_check
+                && !mv.is_capture()
+                && !mv.is_promotion()
+                && best_score > -MATE_BOUND
+                && !board.see_ge(mv, -config.see_quiet_margin * remaining_depth as i32)
+            {
+                all_searched = false;
+                continue;
+            }
+
+            if legality.is_none() && mv.is_castling() && board.is_castling_through_check(mv) {
+                continue;
+            }
 
-            let unmake_move = self.make_move(*mv);
+            let unmake_move = board.make_move(mv);
 
             // Filter illegal moves
-            if self.is_king_in_check(self.opposite_turn()) {
-                self.unmake_move(unmake_move);
+            if legality.is_none() && board.is_king_in_check(board.opposite_turn()) {
+                board.unmake_move(unmake_move);
                 continue;
             };
-            found_legal = true;
+            legal_moves += 1;
+            self.move_stack[ply] = Some(mv);
 
-            let score: i32;
+            // PVS: only the first move gets the full window, the others have to
+            // prove they beat alpha in a null window first
+            let full_window = !config.pvs || legal_moves == 1;
+            let window_beta = if full_window { beta } else { alpha + 1 };
 
-            let can_lmr = !mv.is_capture() && index >= 4 && remaining_depth_next >= 3 && is_lmr;
+            let can_lmr = !mv.is_capture()
+                && move_count > config.lmr_min_move_index
+                && remaining_depth_next >= config.lmr_min_depth
+                && config.lmr;
 
-            if !can_lmr {
-                score = -self.alpha_beta(
-                    ply + 1,
-                    remaining_depth - 1,
-                    -beta,
-                    -alpha,
-                    tt,
-                    is_alpha_beta,
-                    is_tt,
-                    is_null_move_pruning,
-                    is_lmr,
-                    is_quiesense,
-                    killer_moves,
-                );
-            } else {
+            let reduced_score = if can_lmr {
                 // Reduction
                 all_searched = false;
 
-                let reduction = 1;
+                let reduction = config.lmr_reduction;
                 // let reduction = if remaining_depth >= 6 { 2 } else { 1 };
-                let reduced_remaining = remaining_depth_next - reduction;
-
-                let reduced_score = -self.alpha_beta(
+                let reduced_remaining = (remaining_depth_next - reduction).max(0);
+                let reduced_config = SearchConfig {
+                    quiescence: false,
+                    ..*config
+                };
+
+                Some(-self.alpha_beta(
+                    board,
                     ply + 1,
                     reduced_remaining,
-                    -beta,
+                    -window_beta,
                     -alpha,
-                    tt,
-                    is_alpha_beta,
-                    is_tt,
-                    is_null_move_pruning,
-                    is_lmr,
-                    false,
-                    killer_moves,
-                );
-
-                if reduced_score >= alpha {
-                    score = -self.alpha_beta(
+                    &reduced_config,
+                ))
+            } else {
+                None
+            };
+
+            let score = match reduced_score {
+                Some(score) if score <= alpha => score,
+                _ => {
+                    let score = -self.alpha_beta(
+                        board,
                         ply + 1,
-                        remaining_depth - 1,
-                        -beta,
+                        remaining_depth_next,
+                        -window_beta,
                         -alpha,
-                        tt,
-                        is_alpha_beta,
-                        is_tt,
-                        is_null_move_pruning,
-                        is_lmr,
-                        is_quiesense,
-                        killer_moves,
+                        config,
                     );
-                } else {
-                    score = reduced_score;
+
+                    if !full_window && score > alpha && score < beta {
+                        -self.alpha_beta(
+                            board,
+                            ply + 1,
+                            remaining_depth_next,
+                            -beta,
+                            -alpha,
+                            config,
+                        )
+                    } else {
+                        score
+                    }
                 }
-            }
+            };
 
-            self.unmake_move(unmake_move);
+            board.unmake_move(unmake_move);
 
             if score > best_score {
                 best_score = score;
-                best_move = *mv;
+                best_move = Some(mv);
+                self.update_pv(ply, mv);
             }
             alpha = alpha.max(best_score);
 
-            if alpha >= beta && is_alpha_beta {
+            if alpha >= beta && config.alpha_beta {
                 if !mv.is_capture() {
-                    if let Some(killer_move_1) = killer_moves[ply][0] {
-                        if *mv != killer_move_1 {
-                            killer_moves[ply][1] = Some(killer_move_1);
-                            killer_moves[ply][0] = Some(*mv);
+                    self.history.update(mv, &failed_quiets, &previous, remaining_depth);
+
+                    if let Some(killer_move_1) = self.killer_moves[ply][0] {
+                        if mv != killer_move_1 {
+                            self.killer_moves[ply][1] = Some(killer_move_1);
+                            self.killer_moves[ply][0] = Some(mv);
                         }
                     } else {
-                        killer_moves[ply][0] = Some(*mv);
+                        self.killer_moves[ply][0] = Some(mv);
                     }
                 }
                 all_searched = false;
                 break; // Alpha Cutoff
             }
+
+            if !mv.is_capture() {
+                failed_quiets.push(mv);
+            }
         } //
 
-        if !found_legal {
-            if self.is_king_in_check(self.turn) {
-                let mate: i32 = 30_000;
-                best_score = -mate - (remaining_depth as i32);
+        if legal_moves == 0 {
+            if in_check {
+                best_score = -MATE_SCORE + ply as i32;
             } else {
                 best_score = 0; // Stalemate
             }
         };
 
-        let tt_score = if best_score.abs() > 29_000 {
-            best_score + (ply as i32)
-        } else {
-            best_score
-        };
+        // The scores below an interrupted search are meaningless
+        if self.is_interrupted() {
+            return best_score;
+        }
 
-        if is_tt {
-            // The Move isn't Mate
-            tt.store(
-                self.hash,
+        if config.tt {
+            // The bound comes from the score before it is made relative to this node
+            let bound = Bound::new(best_score, orig_alpha, orig_beta, all_searched);
+            self.tt.store(
+                board.hash,
                 remaining_depth as i8,
-                tt_score,
-                orig_alpha,
-                orig_beta,
+                score_to_tt(best_score, ply),
+                bound,
                 best_move,
-                all_searched,
             );
         };
 
         return best_score;
     } //
 
-    pub fn engine_singlethread(
-        &mut self,
-        max_depth: i32,
-        is_alpha_beta: bool,
-        is_tt: bool,
-        is_null_move_pruning: bool,
-        is_lmr: bool,
-        is_quiesense: bool,
-        is_move_ordering: bool,
-        maximum_time: Duration,
-        tt_global: Option<&mut TranspositionTable>,
-    ) -> Move {
-        let moves = self.generate_moves();
-        let start_time = std::time::Instant::now();
+    /// `mv` is the new best move at `ply`, followed by the line of its reply.
+    fn update_pv(&mut self, ply: usize, mv: Move) {
+        let (line, rest) = self.pv_table.split_at_mut(ply + 1);
+        let line = &mut line[ply];
 
-        let mut searched_depth = 0;
-        let mut best_stable_move = moves[0];
-        let mut best_move = moves[0];
+        line.clear();
+        line.push(mv);
+        line.extend_from_slice(&rest[0]);
+    } //
 
-        let mut tt = match tt_global {
-            Some(tt) => tt,
-            None => &mut TranspositionTable::new(20),
-        };
+    /// A PV cut short by a TT hit goes on with the moves stored in the table,
+    /// as long as they are legal and the line doesn't repeat.
+    fn extend_pv_from_tt(&self, board: &mut Board, pv: &mut Vec<Move>, depth: usize) {
+        let mut undos: Vec<_> = pv.iter().map(|mv| board.make_move(*mv)).collect();
 
-        // let mut tt = TranspositionTable::new(20);
-        let mut killer_moves: [[Option<Move>; 2]; 128] = [[None; 2]; 128];
+        while pv.len() < depth && !board.is_draw() {
+            let Some(entry) = self.tt.probe(board.hash) else {
+                break;
+            };
+            if entry.bound == Bound::Upper {
+                break;
+            }
 
-        let mut root_moves = vec![];
+            let legal = board.generate_moves();
+            let Some(mv) = legal.iter().copied().find(|mv| mv.compact() == entry.best_move) else {
+                break;
+            };
 
-        moves.iter().for_each(|mv| root_moves.push((*mv, 0)));
+            pv.push(mv);
+            undos.push(board.make_move(mv));
+        }
 
-        for current_depth in 1..=max_depth {
-            let mut alpha = -30_000;
-            let beta = 30_000;
-            let mut best_score = -30_000;
+        for undo in undos.into_iter().rev() {
+            board.unmake_move(undo);
+        }
+    } //
 
-            for (mv, prev_score) in &mut root_moves {
-                let unmake_move = self.make_move(*mv);
+    /// One iteration over the root moves within `(alpha, beta)`, the line of the best
+    /// move is left in `pv_table[0]`.
+    fn search_root(
+        &mut self,
+        board: &mut Board,
+        root_moves: &mut [(Move, i32)],
+        depth: i32,
+        mut alpha: i32,
+        beta: i32,
+        config: &SearchConfig,
+    ) -> i32 {
+        let mut best_score = -MATE_SCORE;
+        self.pv_table[0].clear();
 
-                let score = -self.alpha_beta(
-                    1,
-                    (current_depth - 1) as i8,
-                    -beta,
-                    -alpha,
-                    &mut tt,
-                    is_alpha_beta,
-                    is_tt,
-                    is_null_move_pruning,
-                    is_lmr,
-                    is_quiesense,
-                    &mut killer_moves,
-                );
-
-                *prev_score = score;
-
-                if score > best_score {
-                    best_score = score;
-                    best_move = *mv;
-                }
+        for (index, (mv, prev_score)) in root_moves.iter_mut().enumerate() {
+            let unmake_move = board.make_move(*mv);
+            self.move_stack[0] = Some(*mv);
 
-                alpha = alpha.max(score);
+            let score = if index == 0 || !config.pvs {
+                -self.alpha_beta(board, 1, (depth - 1) as i8, -beta, -alpha, config)
+            } else {
+                let score =
+                    -self.alpha_beta(board, 1, (depth - 1) as i8, -alpha - 1, -alpha, config);
 
-                self.unmake_move(unmake_move);
-            } //
+                if score > alpha && score < beta {
+                    -self.alpha_beta(board, 1, (depth - 1) as i8, -beta, -alpha, config)
+                } else {
+                    score
+                }
+            };
 
-            if is_move_ordering {
-                root_moves.sort_by_key(|(_, score)| -*score);
-            }
+            board.unmake_move(unmake_move);
+            *prev_score = score;
 
-            if IS_STOP.load(Ordering::Relaxed) || start_time.elapsed() > maximum_time {
-                return best_stable_move;
+            if score > best_score {
+                best_score = score;
+                self.update_pv(0, *mv);
             }
+            alpha = alpha.max(score);
 
-            // uci info print
-            println!(
-                "info depth {current_depth} score cp {best_score} nodes {} time {} pv {}",
-                NODE_COUNT.load(Ordering::Relaxed),
-                start_time.elapsed().as_millis(),
-                best_move.to_uci()
-            );
-            if best_score > 29_900 {
-                return best_move;
+            if alpha >= beta || self.is_interrupted() {
+                break;
             }
+        } //
 
-            searched_depth = current_depth;
-            best_stable_move = best_move;
+        if config.move_ordering {
+            root_moves.sort_by_key(|(_, score)| -*score);
         }
 
-        dbg!(searched_depth);
-        dbg!(NODE_COUNT.load(Ordering::Relaxed));
-        return best_stable_move;
+        best_score
     } //
 
-    pub fn engine(
+    /// Iterative deepening search of `board` within `limits`, the result comes from
+    /// the last completed iteration.
+    fn iterative_deepening(
         &mut self,
-        max_depth: i32,
-        is_alpha_beta: bool,
-        is_tt: bool,
-        is_null_move_pruning: bool,
-        is_lmr: bool,
-        is_quiesense: bool,
-        is_move_ordering: bool,
-        maximum_time: Duration,
-        tt: Option<&mut TranspositionTable>,
-    ) -> Move {
-        if let Some(opening) = self.probe_opening() {
-            return opening;
+        board: &mut Board,
+        limits: &SearchLimits,
+        config: &SearchConfig,
+    ) -> SearchResult {
+        let moves = board.generate_moves();
+        let start_time = Instant::now();
+
+        self.nodes = 0;
+        self.node_limit = limits.max_nodes();
+        self.killer_moves = [[None; 2]; Self::MAX_PLY];
+        self.timed_out = false;
+        self.out_of_nodes = false;
+        self.deadline = limits
+            .time_budget(board.turn)
+            .map(|budget| start_time + budget);
+
+        let max_depth = limits.max_depth();
+
+        // Checkmated or stalemated, there is nothing to search
+        if moves.is_empty() {
+            return SearchResult {
+                best_move: None,
+                ponder_move: None,
+                score: match board.is_king_in_check(board.turn) {
+                    true => -MATE_SCORE,
+                    false => 0,
+                },
+                depth: 0,
+                pv: Vec::new(),
+                nodes: 0,
+            };
         }
 
-        self.engine_singlethread(
-            max_depth,
-            is_alpha_beta,
-            is_tt,
-            is_null_move_pruning,
-            is_lmr,
-            is_quiesense,
-            is_move_ordering,
-            maximum_time,
-            tt,
-        )
-    } //
-
-    pub fn perft(&mut self, depth: i32, max_depth: i32) -> i64 {
-        if depth == max_depth {
-            return 1;
-        }
-        let mut moves = SmallVec::new();
-        self.generate_pesudo_moves(&mut moves);
+        let mut result = SearchResult {
+            best_move: Some(moves[0]),
+            ponder_move: None,
+            score: 0,
+            depth: 0,
+            pv: vec![moves[0]],
+            nodes: 0,
+        };
+        let mut root_moves: Vec<(Move, i32)> = moves.iter().map(|mv| (*mv, 0)).collect();
+        let mut score: i32 = 0;
+
+        // Half of the helpers skip the first iteration, so the threads spread over
+        // different depths
+        let first_depth = (1 + (self.id % 2) as i32).min(max_depth);
+
+        for current_depth in first_depth..=max_depth {
+            // Aspiration window around the previous score, widened until the score falls inside
+            let mut delta = config.aspiration_window;
+            let (mut alpha, mut beta) = if config.aspiration_windows
+                && current_depth > 1
+                && score.abs() < MATE_BOUND
+            {
+                (score - delta, score + delta)
+            } else {
+                (-MATE_SCORE, MATE_SCORE)
+            };
 
-        let mut nodes = 0;
+            loop {
+                score = self.search_root(board, &mut root_moves, current_depth, alpha, beta, config);
 
-        let opposite_turn = self.opposite_turn();
-        let current_turn = self.turn; // Will be the opposite one making the move
+                if self.is_interrupted() {
+                    break;
+                }
 
-        for mv in moves {
-            if mv.is_castling() {
-                match mv.to() {
-                    6 => {
-                        if self.is_square_attacked(6, opposite_turn) {
-                            continue;
-                        } else if self.is_square_attacked(5, opposite_turn) {
-                            continue;
-                        } else if self.is_square_attacked(4, opposite_turn) {
-                            continue;
-                        }
-                    }
-                    2 => {
-                        if self.is_square_attacked(2, opposite_turn) {
-                            continue;
-                        } else if self.is_square_attacked(3, opposite_turn) {
-                            continue;
-                        } else if self.is_square_attacked(4, opposite_turn) {
-                            continue;
-                        }
-                    }
-                    58 => {
-                        if self.is_square_attacked(58, opposite_turn) {
-                            continue;
-                        } else if self.is_square_attacked(59, opposite_turn) {
-                            continue;
-                        } else if self.is_square_attacked(60, opposite_turn) {
-                            continue;
-                        }
-                    }
-                    62 => {
-                        if self.is_square_attacked(62, opposite_turn) {
-                            continue;
-                        } else if self.is_square_attacked(61, opposite_turn) {
-                            continue;
-                        } else if self.is_square_attacked(60, opposite_turn) {
-                            continue;
-                        }
-                    }
-                    _ => (),
+                delta *= 2;
+                if score <= alpha {
+                    alpha = (score - delta).max(-MATE_SCORE);
+                } else if score >= beta {
+                    beta = (score + delta).min(MATE_SCORE);
+                } else {
+                    break;
                 }
-            };
+            }
 
-            // Turn switches here
-            let unmake = self.make_move(mv);
+            self.publish_nodes();
+            result.nodes = self.nodes;
 
-            if self.is_king_in_check(current_turn) {
-                self.unmake_move(unmake);
-                continue;
+            // An interrupted iteration is incomplete, keep the previous one
+            if self.is_interrupted() {
+                break;
             }
 
-            let inner_nodes = self.perft(depth + 1, max_depth);
+            let mut pv = self.pv_table[0].clone();
+            self.extend_pv_from_tt(board, &mut pv, current_depth as usize);
 
-            // Turn switches back
-            self.unmake_move(unmake);
+            result.best_move = pv.first().copied();
+            result.ponder_move = pv.get(1).copied();
+            result.score = score;
+            result.depth = current_depth;
+            result.pv = pv;
 
-            if depth == 0 {
-                println!("{} {}", mv.to_uci(), inner_nodes);
+            if self.id == 0 && config.print_info {
+                self.print_info(&result, start_time);
             }
 
-            nodes += inner_nodes;
+            if score > MATE_BOUND
+                || self.total_nodes() >= self.node_limit
+                || self.is_out_of_time()
+            {
+                break;
+            }
         }
 
-        nodes
+        result
     } //
-}
\ No newline at end of file
+} //
@@ -0,0 +1,349 @@
+use crate::board::pieces::PieceType;
+use crate::board::{Board, Turn, square_name};
+use std::fmt;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FenField {
+    Placement,
+    SideToMove,
+    Castling,
+    EnPassant,
+    HalfmoveClock,
+    FullmoveNumber,
+    /// Anything after the fullmove number
+    Trailing,
+} //
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FenErrorKind {
+    Missing,
+    TooManyFields(usize),
+    WrongRankCount(usize),
+    WrongFileCount { rank: usize, files: usize },
+    InvalidPiece(char),
+    KingCount { white: u32, black: u32 },
+    PawnOnBackRank(usize),
+    OpponentInCheck,
+    InvalidValue(String),
+    MissingCastlingPieces(char),
+    EnPassantWithoutPawn(usize),
+} //
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FenError {
+    pub field: FenField,
+    pub kind: FenErrorKind,
+} //
+
+impl FenError {
+    fn new(field: FenField, kind: FenErrorKind) -> FenError {
+        FenError { field, kind }
+    }
+} //
+
+impl fmt::Display for FenField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FenField::Placement => "piece placement",
+            FenField::SideToMove => "side to move",
+            FenField::Castling => "castling rights",
+            FenField::EnPassant => "en passant square",
+            FenField::HalfmoveClock => "halfmove clock",
+            FenField::FullmoveNumber => "fullmove number",
+            FenField::Trailing => "trailing fields",
+        };
+        f.write_str(name)
+    }
+} //
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid FEN {}: ", self.field)?;
+        match &self.kind {
+            FenErrorKind::Missing => write!(f, "field is missing"),
+            FenErrorKind::TooManyFields(count) => {
+                write!(f, "expected at most 6 fields, found {count}")
+            }
+            FenErrorKind::WrongRankCount(count) => write!(f, "expected 8 ranks, found {count}"),
+            FenErrorKind::WrongFileCount { rank, files } => {
+                write!(f, "rank {} describes {files} files instead of 8", rank + 1)
+            }
+            FenErrorKind::InvalidPiece(c) => write!(f, "unexpected character '{c}'"),
+            FenErrorKind::KingCount { white, black } => write!(
+                f,
+                "expected one king per side, found {white} white and {black} black"
+            ),
+            FenErrorKind::PawnOnBackRank(sq) => {
+                write!(f, "pawn on back rank square {}", square_name(*sq))
+            }
+            FenErrorKind::OpponentInCheck => write!(f, "the side not to move is in check"),
+            FenErrorKind::InvalidValue(value) => write!(f, "unexpected value '{value}'"),
+            FenErrorKind::MissingCastlingPieces(c) => {
+                write!(f, "'{c}' requires the king and rook on their home squares")
+            }
+            FenErrorKind::EnPassantWithoutPawn(sq) => write!(
+                f,
+                "no double-pushed pawn passed over {}",
+                square_name(*sq)
+            ),
+        }
+    }
+} //
+
+impl std::error::Error for FenError {}
+
+fn piece_from_char(c: char) -> Option<PieceType> {
+    match c {
+        'P' => Some(PieceType::WhitePawn),
+        'N' => Some(PieceType::WhiteKnight),
+        'B' => Some(PieceType::WhiteBishop),
+        'R' => Some(PieceType::WhiteRook),
+        'Q' => Some(PieceType::WhiteQueen),
+        'K' => Some(PieceType::WhiteKing),
+        'p' => Some(PieceType::BlackPawn),
+        'n' => Some(PieceType::BlackKnight),
+        'b' => Some(PieceType::BlackBishop),
+        'r' => Some(PieceType::BlackRook),
+        'q' => Some(PieceType::BlackQueen),
+        'k' => Some(PieceType::BlackKing),
+        _ => None,
+    }
+} //
+
+impl Board {
+    /// Parses a FEN string, validating every field.
+    ///
+    /// The halfmove clock and fullmove number may be omitted and default to 0 and 1,
+    /// castling rights and en passant may be omitted as well and default to `-`.
+    pub fn from_fen(fen: &str) -> Result<Board, FenError> {
+        let fields = fen.split_ascii_whitespace().collect::<Vec<_>>();
+
+        if fields.len() > 6 {
+            return Err(FenError::new(
+                FenField::Trailing,
+                FenErrorKind::TooManyFields(fields.len()),
+            ));
+        }
+
+        let mut board = Board::new();
+        board.reset_to_zero();
+
+        let placement = fields
+            .first()
+            .ok_or(FenError::new(FenField::Placement, FenErrorKind::Missing))?;
+        board.parse_placement(placement)?;
+
+        board.turn = match fields.get(1) {
+            Some(&"w") => Turn::WHITE,
+            Some(&"b") => Turn::BLACK,
+            Some(other) => {
+                return Err(FenError::new(
+                    FenField::SideToMove,
+                    FenErrorKind::InvalidValue(other.to_string()),
+                ));
+            }
+            None => return Err(FenError::new(FenField::SideToMove, FenErrorKind::Missing)),
+        };
+
+        if board.is_king_in_check(board.opposite_turn()) {
+            return Err(FenError::new(
+                FenField::SideToMove,
+                FenErrorKind::OpponentInCheck,
+            ));
+        }
+
+        if let Some(castling) = fields.get(2) {
+            board.castling = board.parse_castling(castling)?;
+        }
+
+        if let Some(en_passant) = fields.get(3) {
+            board.en_passant = board.parse_en_passant(en_passant)?;
+        }
+
+        if let Some(halfmove_clock) = fields.get(4) {
+            board.halfmove_clock = halfmove_clock.parse().map_err(|_| {
+                FenError::new(
+                    FenField::HalfmoveClock,
+                    FenErrorKind::InvalidValue(halfmove_clock.to_string()),
+                )
+            })?;
+        }
+
+        if let Some(fullmove_number) = fields.get(5) {
+            board.fullmove_number = match fullmove_number.parse() {
+                Ok(number) if number >= 1 => number,
+                _ => {
+                    return Err(FenError::new(
+                        FenField::FullmoveNumber,
+                        FenErrorKind::InvalidValue(fullmove_number.to_string()),
+                    ));
+                }
+            };
+        }
+
+        board.occupied = board.get_all_bits();
+        board.piece_at = board.generate_piece_at();
+        board.hash = board.compute_hash();
+
+        board.mat_eval = board.pieces_score();
+        board.mobility_eval = board.generate_mobility_eval();
+        let (mg_score, eg_score) = board.generate_pst_score();
+        board.mg_pst_eval = mg_score;
+        board.eg_pst_eval = eg_score;
+
+        board.history = vec![board.hash];
+        board.number_of_pieces = board.generate_pieces_count() as usize;
+        board.number_of_pawns = board.generate_pawns_count() as usize;
+
+        Ok(board)
+    } //
+
+    fn parse_placement(&mut self, placement: &str) -> Result<(), FenError> {
+        let rows: Vec<&str> = placement.split('/').collect();
+
+        if rows.len() != 8 {
+            return Err(FenError::new(
+                FenField::Placement,
+                FenErrorKind::WrongRankCount(rows.len()),
+            ));
+        }
+
+        for (row, rank_str) in rows.iter().enumerate() {
+            let rank = 7 - row;
+            let mut file: usize = 0;
+
+            for c in rank_str.chars() {
+                if let Some(number @ 1..=8) = c.to_digit(10) {
+                    file += number as usize;
+                    continue;
+                }
+
+                let piece = piece_from_char(c)
+                    .ok_or(FenError::new(FenField::Placement, FenErrorKind::InvalidPiece(c)))?;
+
+                if file < 8 {
+                    self.bitboards[piece.piece_index()].0 |= 1u64 << (rank * 8 + file);
+                }
+                file += 1;
+            }
+
+            if file != 8 {
+                return Err(FenError::new(
+                    FenField::Placement,
+                    FenErrorKind::WrongFileCount { rank, files: file },
+                ));
+            }
+        }
+
+        let white_kings = self.bitboards[PieceType::WhiteKing.piece_index()].0.count_ones();
+        let black_kings = self.bitboards[PieceType::BlackKing.piece_index()].0.count_ones();
+
+        if white_kings != 1 || black_kings != 1 {
+            return Err(FenError::new(
+                FenField::Placement,
+                FenErrorKind::KingCount {
+                    white: white_kings,
+                    black: black_kings,
+                },
+            ));
+        }
+
+        let pawns = self.bitboards[PieceType::WhitePawn.piece_index()].0
+            | self.bitboards[PieceType::BlackPawn.piece_index()].0;
+        let back_rank_pawns = pawns & 0xFF000000000000FF;
+
+        if back_rank_pawns != 0 {
+            return Err(FenError::new(
+                FenField::Placement,
+                FenErrorKind::PawnOnBackRank(back_rank_pawns.trailing_zeros() as usize),
+            ));
+        }
+
+        self.occupied = self.get_all_bits();
+
+        Ok(())
+    } //
+
+    fn parse_castling(&self, castling: &str) -> Result<u8, FenError> {
+        if castling == "-" {
+            return Ok(0);
+        }
+
+        let mut rights = 0;
+
+        for c in castling.chars() {
+            // (bit, king piece, king square, rook piece, rook square)
+            let (bit, king, king_sq, rook, rook_sq) = match c {
+                'K' => (0b0001, PieceType::WhiteKing, 4, PieceType::WhiteRook, 7),
+                'Q' => (0b0010, PieceType::WhiteKing, 4, PieceType::WhiteRook, 0),
+                'k' => (0b0100, PieceType::BlackKing, 60, PieceType::BlackRook, 63),
+                'q' => (0b1000, PieceType::BlackKing, 60, PieceType::BlackRook, 56),
+                _ => {
+                    return Err(FenError::new(
+                        FenField::Castling,
+                        FenErrorKind::InvalidValue(castling.to_string()),
+                    ));
+                }
+            };
+
+            if rights & bit != 0 {
+                return Err(FenError::new(
+                    FenField::Castling,
+                    FenErrorKind::InvalidValue(castling.to_string()),
+                ));
+            }
+
+            let king_home = self.bitboards[king.piece_index()].0 & (1u64 << king_sq) != 0;
+            let rook_home = self.bitboards[rook.piece_index()].0 & (1u64 << rook_sq) != 0;
+
+            if !king_home || !rook_home {
+                return Err(FenError::new(
+                    FenField::Castling,
+                    FenErrorKind::MissingCastlingPieces(c),
+                ));
+            }
+
+            rights |= bit;
+        }
+
+        Ok(rights)
+    } //
+
+    fn parse_en_passant(&self, en_passant: &str) -> Result<Option<usize>, FenError> {
+        if en_passant == "-" {
+            return Ok(None);
+        }
+
+        let invalid = || {
+            FenError::new(
+                FenField::EnPassant,
+                FenErrorKind::InvalidValue(en_passant.to_string()),
+            )
+        };
+
+        let bytes = en_passant.as_bytes();
+        if bytes.len() != 2 || !(b'a'..=b'h').contains(&bytes[0]) {
+            return Err(invalid());
+        }
+
+        let file = (bytes[0] - b'a') as usize;
+
+        // The target square sits behind the pawn that has just double pushed
+        let (square, pawn_square, pawn) = match (self.turn, bytes[1]) {
+            (Turn::WHITE, b'6') => (40 + file, 32 + file, PieceType::BlackPawn),
+            (Turn::BLACK, b'3') => (16 + file, 24 + file, PieceType::WhitePawn),
+            _ => return Err(invalid()),
+        };
+
+        if self.occupied.0 & (1u64 << square) != 0
+            || self.bitboards[pawn.piece_index()].0 & (1u64 << pawn_square) == 0
+        {
+            return Err(FenError::new(
+                FenField::EnPassant,
+                FenErrorKind::EnPassantWithoutPawn(square),
+            ));
+        }
+
+        Ok(Some(square))
+    } //
+} //
@@ -0,0 +1,94 @@
+use crate::board::Move;
+
+/// Every entry stays within `-MAX_HISTORY..=MAX_HISTORY`, updates shrink as an
+/// entry gets close to the bound.
+const MAX_HISTORY: i32 = 16_384;
+
+/// Indexed by moved piece and to square.
+type PieceToHistory = [[i16; 64]; 12];
+
+/// Quiet move ordering statistics of one search thread, kept across searches.
+pub struct History {
+    /// Butterfly history of every quiet move
+    butterfly: PieceToHistory,
+    /// The quiet move that refuted each move, indexed by that move's piece and to square
+    countermoves: [[Option<Move>; 64]; 12],
+    /// History of a quiet move following another move, indexed by the piece and to
+    /// square of the move one or two plies back
+    continuation: Vec<[PieceToHistory; 64]>,
+} //
+
+impl Default for History {
+    fn default() -> Self {
+        History {
+            butterfly: [[0; 64]; 12],
+            countermoves: [[None; 64]; 12],
+            continuation: vec![[[[0; 64]; 12]; 64]; 12],
+        }
+    }
+} //
+
+/// Moves up to two plies before the current node, `None` at the root or after a
+/// null move.
+pub type PreviousMoves = [Option<Move>; 2];
+
+impl History {
+    pub fn clear(&mut self) {
+        self.butterfly = [[0; 64]; 12];
+        self.countermoves = [[None; 64]; 12];
+        self.continuation.fill([[[0; 64]; 12]; 64]);
+    } //
+
+    pub fn countermove(&self, previous: Option<Move>) -> Option<Move> {
+        previous
+            .and_then(|previous| self.countermoves[previous.piece().piece_index()][previous.to()])
+    } //
+
+    /// Ordering score of a quiet move, the sum of its butterfly and continuation histories.
+    pub fn quiet_score(&self, mv: Move, previous: &PreviousMoves) -> i32 {
+        let (piece, to) = (mv.piece().piece_index(), mv.to());
+
+        let continuation: i32 = previous
+            .iter()
+            .flatten()
+            .map(|previous| {
+                self.continuation[previous.piece().piece_index()][previous.to()][piece][to] as i32
+            })
+            .sum();
+
+        self.butterfly[piece][to] as i32 + continuation
+    } //
+
+    /// `best` caused a beta cutoff after the quiet moves in `failed` were searched
+    /// without one.
+    pub fn update(&mut self, best: Move, failed: &[Move], previous: &PreviousMoves, depth: i8) {
+        let depth = depth as i32;
+        let bonus = (32 * depth * depth).min(1_600);
+
+        self.update_quiet(best, previous, bonus);
+        for mv in failed {
+            self.update_quiet(*mv, previous, -bonus);
+        }
+
+        if let Some(previous) = previous[0] {
+            self.countermoves[previous.piece().piece_index()][previous.to()] = Some(best);
+        }
+    } //
+
+    fn update_quiet(&mut self, mv: Move, previous: &PreviousMoves, bonus: i32) {
+        let (piece, to) = (mv.piece().piece_index(), mv.to());
+
+        gravity(&mut self.butterfly[piece][to], bonus);
+        for previous in previous.iter().flatten() {
+            let table = &mut self.continuation[previous.piece().piece_index()][previous.to()];
+            gravity(&mut table[piece][to], bonus);
+        }
+    } //
+} //
+
+/// Moves the entry towards the bonus, by less the closer it already is to the bound.
+#[inline(always)]
+fn gravity(entry: &mut i16, bonus: i32) {
+    let value = *entry as i32;
+    *entry = (value + bonus - value * bonus.abs() / MAX_HISTORY) as i16;
+} //
@@ -0,0 +1,207 @@
+use crate::board::bishop_magic::bishop_attacks;
+use crate::board::pieces::PieceType;
+use crate::board::rook_magic::rook_attacks;
+use crate::board::{Board, Move, Turn};
+use smallvec::SmallVec;
+
+/// Squares strictly between `a` and `b` when they share a rank, file or diagonal,
+/// otherwise none.
+#[inline(always)]
+fn between(a: usize, b: usize) -> u64 {
+    let (a_bb, b_bb) = (1u64 << a, 1u64 << b);
+
+    if rook_attacks(a, 0) & b_bb != 0 {
+        rook_attacks(a, b_bb) & rook_attacks(b, a_bb)
+    } else if bishop_attacks(a, 0) & b_bb != 0 {
+        bishop_attacks(a, b_bb) & bishop_attacks(b, a_bb)
+    } else {
+        0
+    }
+} //
+
+/// Checkers, pinned pieces and evasion squares of the side to move, computed once
+/// per node. Tells which pseudo-legal moves are legal without making them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Legality {
+    /// `None` in positions without a king of the side to move, where every move is legal
+    king: Option<usize>,
+    checkers: u64,
+    /// Own pieces shielding the king from an enemy slider
+    pinned: u64,
+    /// Destinations that resolve a single check, every square when not in check
+    evasions: u64,
+} //
+
+impl Legality {
+    pub fn in_check(&self) -> bool {
+        self.checkers != 0
+    } //
+
+    /// Whether the pseudo-legal `mv` leaves the own king safe.
+    pub fn is_legal(&self, board: &Board, mv: Move) -> bool {
+        let Some(king) = self.king else {
+            return true;
+        };
+        let (from, to) = (mv.from(), mv.to());
+
+        if from == king {
+            // Castling may neither start in, pass through nor end in check
+            if mv.is_castling() {
+                let path = between(from, to) | (1u64 << to);
+                return !self.in_check() && !board.is_any_attacked(path, 1u64 << king);
+            }
+            return !board.is_any_attacked(1u64 << to, 1u64 << king);
+        }
+
+        if self.checkers.count_ones() > 1 {
+            return false;
+        }
+
+        // Removes two pawns from the rank of the king at once, check every attacker
+        if mv.is_en_passant() {
+            let captured = match board.turn {
+                Turn::WHITE => to - 8,
+                Turn::BLACK => to + 8,
+            };
+            let occupied = (board.occupied.0 ^ (1u64 << from) ^ (1u64 << captured)) | (1u64 << to);
+            let enemies = board.get_enemy_pieces().0 & !(1u64 << captured);
+            return board.attackers_to(king, occupied) & enemies & occupied == 0;
+        }
+
+        if self.evasions & (1u64 << to) == 0 {
+            return false;
+        }
+
+        // A pinned piece stays on the line between the king and the pinner
+        self.pinned & (1u64 << from) == 0
+            || between(king, to) & (1u64 << from) != 0
+            || between(king, from) & (1u64 << to) != 0
+    } //
+} //
+
+impl Board {
+    pub fn legality(&self) -> Legality {
+        let king_piece = match self.turn {
+            Turn::WHITE => PieceType::WhiteKing,
+            Turn::BLACK => PieceType::BlackKing,
+        };
+        let king_bb = self.bitboards[king_piece.piece_index()].0;
+        if king_bb == 0 {
+            return Legality {
+                king: None,
+                checkers: 0,
+                pinned: 0,
+                evasions: u64::MAX,
+            };
+        }
+
+        let king = king_bb.trailing_zeros() as usize;
+        let occupied = self.occupied.0;
+        let own = self.get_allay_pieces().0;
+        let enemies = self.get_enemy_pieces().0;
+
+        let checkers = self.attackers_to(king, occupied) & enemies;
+        let evasions = match checkers.count_ones() {
+            0 => u64::MAX,
+            1 => checkers | between(king, checkers.trailing_zeros() as usize),
+            _ => 0,
+        };
+
+        // Enemy sliders seeing the king through exactly one own piece pin it
+        let bbs = &self.bitboards;
+        let (bishops_queens, rooks_queens) = match self.turn {
+            Turn::WHITE => (bbs[8].0 | bbs[10].0, bbs[9].0 | bbs[10].0),
+            Turn::BLACK => (bbs[2].0 | bbs[4].0, bbs[3].0 | bbs[4].0),
+        };
+        let mut snipers =
+            (bishop_attacks(king, 0) & bishops_queens) | (rook_attacks(king, 0) & rooks_queens);
+
+        let mut pinned = 0;
+        while snipers != 0 {
+            let sniper = snipers.trailing_zeros() as usize;
+            snipers &= snipers - 1;
+
+            let blockers = between(king, sniper) & occupied;
+            if blockers.count_ones() == 1 {
+                pinned |= blockers & own;
+            }
+        }
+
+        Legality {
+            king: Some(king),
+            checkers,
+            pinned,
+            evasions,
+        }
+    } //
+
+    /// Whether an enemy piece attacks any of `squares` once `ignored` is off the
+    /// board, so the king can't hide behind itself from a slider.
+    fn is_any_attacked(&self, mut squares: u64, ignored: u64) -> bool {
+        let occupied = self.occupied.0 & !ignored;
+        let enemies = self.get_enemy_pieces().0;
+
+        while squares != 0 {
+            let square = squares.trailing_zeros() as usize;
+            squares &= squares - 1;
+
+            if self.attackers_to(square, occupied) & enemies != 0 {
+                return true;
+            }
+        }
+        false
+    } //
+
+    /// Legal moves only, generated without making any of them.
+    pub fn generate_legal_moves(&self, moves: &mut SmallVec<[Move; 256]>) {
+        let legality = self.legality();
+        let own = self.get_allay_pieces().0;
+
+        let mut pseudo = SmallVec::new();
+        self.generate_king_moves(&mut pseudo, !own);
+
+        // In double check only the king may move
+        if legality.evasions != 0 {
+            let targets = !own & legality.evasions;
+            self.generate_knight_moves(&mut pseudo, targets);
+            self.generate_bishop_moves_magics(&mut pseudo, targets);
+            self.generate_rook_moves_magics(&mut pseudo, targets);
+
+            // En passant may capture the checker or block the check, it is verified
+            // on its own either way
+            let en_passant = match (self.en_passant, self.turn) {
+                (Some(square), Turn::WHITE) => 1u64 << (square - 8),
+                (Some(square), Turn::BLACK) => 1u64 << (square + 8),
+                (None, _) => 0,
+            };
+            match self.turn {
+                Turn::WHITE => self.generate_white_pawns_moves(&mut pseudo, targets | en_passant),
+                Turn::BLACK => self.generate_black_pawns_moves(&mut pseudo, targets | en_passant),
+            };
+        }
+
+        moves.extend(pseudo.into_iter().filter(|mv| legality.is_legal(self, *mv)));
+    } //
+
+    /// Leaf nodes `depth` plies below, counted with the legal move generator.
+    pub fn perft_legal(&mut self, depth: i32) -> i64 {
+        if depth <= 0 {
+            return 1;
+        }
+        let mut moves = SmallVec::new();
+        self.generate_legal_moves(&mut moves);
+
+        // Every move is legal, the last ply needs no make and unmake
+        if depth == 1 {
+            return moves.len() as i64;
+        }
+
+        let mut nodes = 0;
+        for mv in moves {
+            let unmake = self.make_move(mv);
+            nodes += self.perft_legal(depth - 1);
+            self.unmake_move(unmake);
+        }
+        nodes
+    } //
+} //
@@ -2,16 +2,30 @@ pub mod bishop_magic;
 pub mod board;
 pub mod constants;
 mod engine;
+pub mod fen;
+pub mod history;
+pub mod legal;
 pub mod move_gen;
+pub mod move_picker;
 mod openings;
+pub mod pgn;
 mod pieces;
 pub mod rook_magic;
+mod san;
+mod see;
+pub mod search;
+pub mod status;
 pub mod tt;
 mod zobrist;
 
 pub use board::Board;
+pub use engine::{Engine, SearchRunning, StopHandle};
+pub use fen::FenError;
+pub use search::{SearchConfig, SearchHandle, SearchLimits, SearchResult};
+pub use status::GameStatus;
 use constants::{PROMO_MASK, PROMO_SHIFT};
 use pieces::PieceType;
+use std::fmt;
 use std::ops::{Deref, DerefMut};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -160,6 +174,56 @@ impl Move {
         Move::new(from, to, piece, capture, false, None, false)
     } //
 
+    /// Parses a move in long algebraic notation (`e2e4`, `e7e8q`) and matches it
+    /// against the legal moves of `board`.
+    ///
+    /// Promotion letters are accepted in either case for both colors.
+    pub fn parse_uci(uci: &str, board: &Board) -> Result<Move, MoveParseError> {
+        // Sliced by bytes below, so no multi-byte character may get that far
+        if let Some(c) = uci.chars().find(|c| !c.is_ascii()) {
+            return Err(MoveParseError::InvalidCharacter(c));
+        }
+
+        let bytes = uci.as_bytes();
+
+        if bytes.len() != 4 && bytes.len() != 5 {
+            return Err(MoveParseError::InvalidLength(bytes.len()));
+        }
+
+        let from = parse_square(&bytes[0..2])
+            .ok_or_else(|| MoveParseError::InvalidSquare(uci[0..2].to_string()))?;
+        let to = parse_square(&bytes[2..4])
+            .ok_or_else(|| MoveParseError::InvalidSquare(uci[2..4].to_string()))?;
+
+        let promotion = match bytes.get(4) {
+            None => None,
+            Some(c) => Some(match c.to_ascii_lowercase() {
+                b'q' => PieceType::WhiteQueen,
+                b'r' => PieceType::WhiteRook,
+                b'b' => PieceType::WhiteBishop,
+                b'n' => PieceType::WhiteKnight,
+                _ => return Err(MoveParseError::InvalidPromotion(*c as char)),
+            }),
+        };
+
+        let mut candidates = board
+            .clone()
+            .generate_moves()
+            .into_iter()
+            .filter(|mv| mv.from() == from && mv.to() == to);
+
+        match promotion {
+            Some(promotion) => candidates
+                .find(|mv| mv.promotion_piece() == Some(promotion))
+                .ok_or_else(|| MoveParseError::Illegal(uci.to_string())),
+            None => match (candidates.next(), candidates.next()) {
+                (Some(mv), None) => Ok(mv),
+                (Some(_), Some(_)) => Err(MoveParseError::Ambiguous(uci.to_string())),
+                (None, _) => Err(MoveParseError::Illegal(uci.to_string())),
+            },
+        }
+    } //
+
     #[inline(always)]
     pub fn is_castling(self) -> bool {
         ((self.0 >> 17) & 1) != 0
@@ -174,6 +238,11 @@ impl Move {
     pub fn is_promotion(self) -> bool {
         ((self.0 >> 18) & 1) != 0
     }
+    /// From, to and promotion piece, enough to tell apart the moves of one position.
+    #[inline(always)]
+    pub fn compact(self) -> u16 {
+        ((self.0 & 0xFFF) | (((self.0 & PROMO_MASK) >> PROMO_SHIFT) << 12)) as u16
+    }
     pub fn promotion_piece(self) -> Option<PieceType> {
         let idx = ((self.0 & PROMO_MASK) >> PROMO_SHIFT) as u8;
         if idx == 0 {
@@ -183,6 +252,49 @@ impl Move {
     }
 } //
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MoveParseError {
+    InvalidLength(usize),
+    InvalidCharacter(char),
+    InvalidSquare(String),
+    InvalidPromotion(char),
+    Illegal(String),
+    Ambiguous(String),
+    InvalidSan(String),
+} //
+
+impl fmt::Display for MoveParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveParseError::InvalidLength(len) => {
+                write!(f, "expected 4 or 5 characters, found {len}")
+            }
+            MoveParseError::InvalidCharacter(c) => write!(f, "invalid character '{c}'"),
+            MoveParseError::InvalidSquare(square) => write!(f, "invalid square '{square}'"),
+            MoveParseError::InvalidPromotion(c) => write!(f, "invalid promotion piece '{c}'"),
+            MoveParseError::Illegal(mv) => write!(f, "illegal move '{mv}'"),
+            MoveParseError::Ambiguous(mv) => write!(f, "ambiguous move '{mv}'"),
+            MoveParseError::InvalidSan(san) => write!(f, "invalid SAN move '{san}'"),
+        }
+    }
+} //
+
+impl std::error::Error for MoveParseError {}
+
+/// Parses a two byte square such as `e4` into a square index.
+fn parse_square(bytes: &[u8]) -> Option<usize> {
+    match bytes {
+        [file @ b'a'..=b'h', rank @ b'1'..=b'8'] => {
+            Some((rank - b'1') as usize * 8 + (file - b'a') as usize)
+        }
+        _ => None,
+    }
+} //
+
+pub(crate) fn square_name(sq: usize) -> String {
+    format!("{}{}", (b'a' + (sq % 8) as u8) as char, sq / 8 + 1)
+} //
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct UnMakeMove {
     from: usize,
@@ -200,6 +312,8 @@ pub struct UnMakeMove {
     eg_pst_eval: i32,
     mobility_eval: i32,
     last_irreversible_move: usize,
+    halfmove_clock: u32,
+    fullmove_number: u32,
     number_of_pieces: usize,
     number_of_pawns: usize,
     promotion_piece: Option<PieceType>,
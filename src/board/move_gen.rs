@@ -1,8 +1,8 @@
 use crate::board::UnMakeMove;
 use crate::board::bishop_magic::bishop_attacks;
 use crate::board::constants::{
-    BLACK_PAWN_ATTACKS, KING_ATTACK_TABLE, KNIGHTS_ATTACK_TABLE, RANK_2, RANK_3, RANK_4, RANK_5,
-    RANK_6, RANK_7, WHITE_PAWN_ATTACKS,
+    BLACK_PAWN_ATTACKS, KING_ATTACK_TABLE, KNIGHTS_ATTACK_TABLE, RANK_1, RANK_2, RANK_3, RANK_4,
+    RANK_5, RANK_6, RANK_7, RANK_8, WHITE_PAWN_ATTACKS,
 };
 use crate::board::pieces::PieceType;
 use crate::board::rook_magic::rook_attacks;
@@ -11,11 +11,11 @@ use crate::board::{Board, Move, Turn};
 use smallvec::SmallVec;
 
 impl Board {
+    /// Knight moves to the squares in `targets`, which must not contain own pieces.
     #[inline(always)]
-    pub fn generate_knight_moves(&self, moves: &mut SmallVec<[Move; 256]>) {
+    pub fn generate_knight_moves(&self, moves: &mut SmallVec<[Move; 256]>, targets: u64) {
         // let mut moves = Vec::new();
         let enemy_bits = self.get_enemy_pieces().0;
-        let allay_bits = self.get_allay_pieces().0;
 
         let (mut knights, piece_type) = match self.turn {
             Turn::WHITE => (
@@ -31,7 +31,7 @@ impl Board {
         while knights != 0 {
             let from = knights.trailing_zeros() as usize;
             knights &= knights - 1;
-            let mut attacks = KNIGHTS_ATTACK_TABLE[from as usize] & !allay_bits;
+            let mut attacks = KNIGHTS_ATTACK_TABLE[from as usize] & targets;
 
             while attacks != 0 {
                 let to = attacks.trailing_zeros() as usize;
@@ -42,11 +42,12 @@ impl Board {
         }
     } //
 
+    /// King moves to the squares in `targets`, castling included when the king's
+    /// destination is one of them.
     #[inline(always)]
-    pub fn generate_king_moves(&self, moves: &mut SmallVec<[Move; 256]>) {
+    pub fn generate_king_moves(&self, moves: &mut SmallVec<[Move; 256]>, targets: u64) {
         // let mut moves = Vec::new();
         let enemy_bits = self.get_enemy_pieces().0;
-        let allay_bits = self.get_allay_pieces().0;
         let occupied = self.occupied.0;
 
         let (king, piece_type) = match self.turn {
@@ -62,12 +63,15 @@ impl Board {
 
         match self.turn {
             Turn::WHITE => {
-                if (self.castling & 0b0001 != 0) && ((((1u64 << 5) | (1u64 << 6)) & occupied) == 0)
+                if (self.castling & 0b0001 != 0)
+                    && ((((1u64 << 5) | (1u64 << 6)) & occupied) == 0)
+                    && targets & (1u64 << 6) != 0
                 {
                     moves.push(Move::new(4, 6, piece_type, false, true, None, false));
                 }
                 if self.castling & 0b0010 != 0
                     && ((((1u64 << 1) | (1u64 << 2) | (1u64 << 3)) & occupied) == 0)
+                    && targets & (1u64 << 2) != 0
                 {
                     moves.push(Move::new(4, 2, piece_type, false, true, None, false));
                 }
@@ -75,11 +79,13 @@ impl Board {
             Turn::BLACK => {
                 if (self.castling & 0b0100 != 0)
                     && ((((1u64 << 61) | (1u64 << 62)) & occupied) == 0)
+                    && targets & (1u64 << 62) != 0
                 {
                     moves.push(Move::new(60, 62, piece_type, false, true, None, false));
                 }
                 if self.castling & 0b1000 != 0
                     && ((((1u64 << 57) | (1u64 << 58) | (1u64 << 59)) & occupied) == 0)
+                    && targets & (1u64 << 58) != 0
                 {
                     moves.push(Move::new(60, 58, piece_type, false, true, None, false));
                 }
@@ -91,7 +97,7 @@ impl Board {
             return;
         }
 
-        let mut attacks = (KING_ATTACK_TABLE[from as usize]) & !allay_bits;
+        let mut attacks = (KING_ATTACK_TABLE[from as usize]) & targets;
 
         while attacks != 0 {
             let to = attacks.trailing_zeros() as usize;
@@ -101,8 +107,10 @@ impl Board {
         }
     } //
 
+    /// Pawn moves to the squares in `targets`. En passant counts as a move to the
+    /// square of the captured pawn.
     #[inline(always)]
-    pub fn generate_white_pawns_moves(&self, moves: &mut SmallVec<[Move; 256]>) {
+    pub fn generate_white_pawns_moves(&self, moves: &mut SmallVec<[Move; 256]>, targets: u64) {
         let blockers = self.occupied.0;
         // let pawn_squares = &self.bitboards[PieceType::WhitePawn.piece_index()];
 
@@ -118,35 +126,40 @@ impl Board {
 
             // single and double jump
             if (blockers & (1u64 << (from + 8))) == 0 {
-                if from + 8 >= 56 {
-                    for piece in [
-                        PieceType::WhiteQueen,
-                        PieceType::WhiteRook,
-                        PieceType::WhiteBishop,
-                        PieceType::WhiteKnight,
-                    ] {
+                if targets & (1u64 << (from + 8)) != 0 {
+                    if from + 8 >= 56 {
+                        for piece in [
+                            PieceType::WhiteQueen,
+                            PieceType::WhiteRook,
+                            PieceType::WhiteBishop,
+                            PieceType::WhiteKnight,
+                        ] {
+                            moves.push(Move::new(
+                                from,
+                                from + 8,
+                                PieceType::WhitePawn,
+                                false,
+                                false,
+                                Some(piece),
+                                false,
+                            ));
+                        }
+                    } else {
                         moves.push(Move::new(
                             from,
                             from + 8,
                             PieceType::WhitePawn,
                             false,
                             false,
-                            Some(piece),
+                            None,
                             false,
                         ));
                     }
-                } else {
-                    moves.push(Move::new(
-                        from,
-                        from + 8,
-                        PieceType::WhitePawn,
-                        false,
-                        false,
-                        None,
-                        false,
-                    ));
                 }
-                if (((1u64 << from) & RANK_2) != 0) && (blockers & (1u64 << (from + 16))) == 0 {
+                if (((1u64 << from) & RANK_2) != 0)
+                    && (blockers & (1u64 << (from + 16))) == 0
+                    && targets & (1u64 << (from + 16)) != 0
+                {
                     moves.push(Move::new(
                         from,
                         from + 16,
@@ -161,11 +174,12 @@ impl Board {
 
             // attacks
             let attacks_bb = WHITE_PAWN_ATTACKS[from as usize];
-            let mut attacks = attacks_bb & enemy_pieces_bb.0;
+            let mut attacks = attacks_bb & enemy_pieces_bb.0 & targets;
 
             if let Some(en_passant_square) = self.en_passant {
                 if attacks_bb & (1u64 << en_passant_square) != 0
                     && ((1u64 << en_passant_square) & RANK_6) != 0
+                    && targets & (1u64 << (en_passant_square - 8)) != 0
                 {
                     moves.push(Move::new(
                         from,
@@ -214,8 +228,10 @@ impl Board {
         }
     } //
 
+    /// Pawn moves to the squares in `targets`. En passant counts as a move to the
+    /// square of the captured pawn.
     #[inline(always)]
-    pub fn generate_black_pawns_moves(&self, moves: &mut SmallVec<[Move; 256]>) {
+    pub fn generate_black_pawns_moves(&self, moves: &mut SmallVec<[Move; 256]>, targets: u64) {
         let blockers = self.occupied.0;
         let enemy_pieces_bb = self.get_all_white_bits();
 
@@ -227,35 +243,40 @@ impl Board {
 
             // single and double jump
             if (blockers & (1u64 << (from - 8))) == 0 {
-                if (from - 8) < 8 {
-                    for piece in [
-                        PieceType::WhiteQueen,
-                        PieceType::WhiteRook,
-                        PieceType::WhiteBishop,
-                        PieceType::WhiteKnight,
-                    ] {
+                if targets & (1u64 << (from - 8)) != 0 {
+                    if (from - 8) < 8 {
+                        for piece in [
+                            PieceType::WhiteQueen,
+                            PieceType::WhiteRook,
+                            PieceType::WhiteBishop,
+                            PieceType::WhiteKnight,
+                        ] {
+                            moves.push(Move::new(
+                                from,
+                                from - 8,
+                                PieceType::BlackPawn,
+                                false,
+                                false,
+                                Some(piece),
+                                false,
+                            ));
+                        }
+                    } else {
                         moves.push(Move::new(
                             from,
                             from - 8,
                             PieceType::BlackPawn,
                             false,
                             false,
-                            Some(piece),
+                            None,
                             false,
                         ));
                     }
-                } else {
-                    moves.push(Move::new(
-                        from,
-                        from - 8,
-                        PieceType::BlackPawn,
-                        false,
-                        false,
-                        None,
-                        false,
-                    ));
                 }
-                if (((1u64 << from) & RANK_7) != 0) && (blockers & (1u64 << (from - 16))) == 0 {
+                if (((1u64 << from) & RANK_7) != 0)
+                    && (blockers & (1u64 << (from - 16))) == 0
+                    && targets & (1u64 << (from - 16)) != 0
+                {
                     moves.push(Move::new(
                         from,
                         from - 16,
@@ -269,11 +290,12 @@ impl Board {
             }
             // attacks
             let attacks_bb = BLACK_PAWN_ATTACKS[from as usize];
-            let mut attacks = attacks_bb & enemy_pieces_bb.0;
+            let mut attacks = attacks_bb & enemy_pieces_bb.0 & targets;
 
             if let Some(en_passant_square) = self.en_passant {
                 if attacks_bb & (1u64 << en_passant_square) != 0
                     && ((1u64 << en_passant_square) & RANK_3) != 0
+                    && targets & (1u64 << (en_passant_square + 8)) != 0
                 {
                     moves.push(Move::new(
                         from,
@@ -322,9 +344,9 @@ impl Board {
         }
     } //
 
+    /// Rook and queen moves along ranks and files to the squares in `targets`.
     #[inline(always)]
-    pub fn generate_rook_moves_magics(&self, moves: &mut SmallVec<[Move; 256]>) {
-        let allay = self.get_allay_pieces().0;
+    pub fn generate_rook_moves_magics(&self, moves: &mut SmallVec<[Move; 256]>, targets: u64) {
         let enemy = self.get_enemy_pieces().0;
         let occupied = self.occupied.0;
 
@@ -344,7 +366,7 @@ impl Board {
             rooks &= rooks - 1;
 
             let attacks_bb = rook_attacks(from as usize, occupied);
-            let mut attacks = attacks_bb & !allay;
+            let mut attacks = attacks_bb & targets;
 
             while attacks != 0 {
                 let to = attacks.trailing_zeros() as usize;
@@ -370,7 +392,7 @@ impl Board {
             queens &= queens - 1;
 
             let attacks_bb = rook_attacks(from as usize, occupied);
-            let mut attacks = attacks_bb & !allay;
+            let mut attacks = attacks_bb & targets;
 
             while attacks != 0 {
                 let to = attacks.trailing_zeros() as usize;
@@ -381,9 +403,9 @@ impl Board {
         }
     } //
 
+    /// Bishop and queen moves along diagonals to the squares in `targets`.
     #[inline(always)]
-    pub fn generate_bishop_moves_magics(&self, moves: &mut SmallVec<[Move; 256]>) {
-        let allay_bits = self.get_allay_pieces();
+    pub fn generate_bishop_moves_magics(&self, moves: &mut SmallVec<[Move; 256]>, targets: u64) {
         let enemy_bits = self.get_enemy_pieces();
         let all_bits = self.occupied.0;
 
@@ -403,7 +425,7 @@ impl Board {
             bishops &= bishops - 1;
 
             let attacks_bb = bishop_attacks(from as usize, all_bits);
-            let mut attacks = attacks_bb & !allay_bits.0;
+            let mut attacks = attacks_bb & targets;
 
             while attacks != 0 {
                 let to = attacks.trailing_zeros() as usize;
@@ -429,7 +451,7 @@ impl Board {
             queens &= queens - 1;
 
             let attacks_bb = bishop_attacks(from as usize, all_bits);
-            let mut attacks = attacks_bb & !allay_bits.0;
+            let mut attacks = attacks_bb & targets;
 
             while attacks != 0 {
                 let to = attacks.trailing_zeros() as usize;
@@ -465,81 +487,258 @@ impl Board {
     } //
 
     #[inline(always)]
-    pub fn generate_pesudo_moves(&self, mut moves: &mut SmallVec<[Move; 256]>) {
-        self.generate_knight_moves(&mut moves);
-        self.generate_bishop_moves_magics(&mut moves);
-        self.generate_rook_moves_magics(&mut moves);
-        self.generate_king_moves(&mut moves);
+    pub fn generate_pesudo_moves(&self, moves: &mut SmallVec<[Move; 256]>) {
+        let targets = !self.get_allay_pieces().0;
+
+        self.generate_knight_moves(moves, targets);
+        self.generate_bishop_moves_magics(moves, targets);
+        self.generate_rook_moves_magics(moves, targets);
+        self.generate_king_moves(moves, targets);
+        self.generate_pawns_moves(moves, targets);
+    } //
 
+    #[inline(always)]
+    fn generate_pawns_moves(&self, moves: &mut SmallVec<[Move; 256]>, targets: u64) {
         match self.turn {
-            Turn::WHITE => self.generate_white_pawns_moves(&mut moves),
-            Turn::BLACK => self.generate_black_pawns_moves(&mut moves),
+            Turn::WHITE => self.generate_white_pawns_moves(moves, targets),
+            Turn::BLACK => self.generate_black_pawns_moves(moves, targets),
         };
     } //
 
-    pub fn generate_moves(&mut self) -> SmallVec<[Move; 256]> {
-        let mut pesudo_moves: SmallVec<[Move; 256]> = SmallVec::new();
-        let mut legal_moves: SmallVec<[Move; 256]> = SmallVec::new();
-
-        self.generate_pesudo_moves(&mut pesudo_moves);
-
-        for mv in pesudo_moves {
-            let opposite_turn = self.opposite_turn();
-
-            if mv.is_castling() {
-                match mv.to() {
-                    6 => {
-                        if self.is_square_attacked(6, opposite_turn) {
-                            continue;
-                        } else if self.is_square_attacked(5, opposite_turn) {
-                            continue;
-                        } else if self.is_square_attacked(4, opposite_turn) {
-                            continue;
-                        }
-                    }
-                    2 => {
-                        if self.is_square_attacked(2, opposite_turn) {
-                            continue;
-                        } else if self.is_square_attacked(3, opposite_turn) {
-                            continue;
-                        } else if self.is_square_attacked(4, opposite_turn) {
-                            continue;
-                        }
-                    }
-                    58 => {
-                        if self.is_square_attacked(58, opposite_turn) {
-                            continue;
-                        } else if self.is_square_attacked(59, opposite_turn) {
-                            continue;
-                        } else if self.is_square_attacked(60, opposite_turn) {
-                            continue;
-                        }
-                    }
-                    62 => {
-                        if self.is_square_attacked(62, opposite_turn) {
-                            continue;
-                        } else if self.is_square_attacked(61, opposite_turn) {
-                            continue;
-                        } else if self.is_square_attacked(60, opposite_turn) {
-                            continue;
-                        }
-                    }
-                    _ => (),
-                }
-            };
-            // Turn switched here
-            let unmake_move = self.make_move(mv);
+    /// Pawn pushes to the last rank, one move for each piece in `pieces`.
+    #[inline(always)]
+    fn generate_push_promotions(&self, moves: &mut SmallVec<[Move; 256]>, pieces: &[PieceType]) {
+        let empty = !self.occupied.0;
+
+        let (mut targets, piece_type) = match self.turn {
+            Turn::WHITE => (
+                ((self.bitboards[PieceType::WhitePawn.piece_index()].0 & RANK_7) << 8) & empty,
+                PieceType::WhitePawn,
+            ),
+            Turn::BLACK => (
+                ((self.bitboards[PieceType::BlackPawn.piece_index()].0 & RANK_2) >> 8) & empty,
+                PieceType::BlackPawn,
+            ),
+        };
 
-            let is_illegal = self.is_king_in_check(self.opposite_turn());
+        while targets != 0 {
+            let to = targets.trailing_zeros() as usize;
+            targets &= targets - 1;
+            let from = match self.turn {
+                Turn::WHITE => to - 8,
+                Turn::BLACK => to + 8,
+            };
 
-            if !is_illegal {
-                legal_moves.push(mv);
+            for &piece in pieces {
+                moves.push(Move::new(
+                    from,
+                    to,
+                    piece_type,
+                    false,
+                    false,
+                    Some(piece),
+                    false,
+                ));
             }
+        }
+    } //
+
+    /// Captures, en passant and capturing promotions included, and pushes promoting
+    /// to a queen. Together with `generate_quiets` these are the pseudo-legal moves.
+    pub fn generate_captures(&self, moves: &mut SmallVec<[Move; 256]>) {
+        let targets = self.get_enemy_pieces().0;
+
+        self.generate_knight_moves(moves, targets);
+        self.generate_bishop_moves_magics(moves, targets);
+        self.generate_rook_moves_magics(moves, targets);
+        self.generate_king_moves(moves, targets);
+        self.generate_pawns_moves(moves, targets);
+        self.generate_push_promotions(moves, &[PieceType::WhiteQueen]);
+    } //
+
+    /// Non-capturing moves, castling and pushes promoting to anything but a queen.
+    pub fn generate_quiets(&self, moves: &mut SmallVec<[Move; 256]>) {
+        let targets = !self.occupied.0;
+
+        self.generate_knight_moves(moves, targets);
+        self.generate_bishop_moves_magics(moves, targets);
+        self.generate_rook_moves_magics(moves, targets);
+        self.generate_king_moves(moves, targets);
+        self.generate_pawns_moves(moves, targets & !(RANK_1 | RANK_8));
+        self.generate_push_promotions(
+            moves,
+            &[
+                PieceType::WhiteRook,
+                PieceType::WhiteBishop,
+                PieceType::WhiteKnight,
+            ],
+        );
+    } //
 
-            // Turn returns back
-            self.unmake_move(unmake_move);
+    /// Quiet moves attacking the enemy king from their destination. Discovered
+    /// checks, castling and promotions are left out.
+    pub fn generate_quiet_checks(&self, moves: &mut SmallVec<[Move; 256]>) {
+        let (enemy_king, pawn_checks_table) = match self.turn {
+            Turn::WHITE => (
+                self.bitboards[PieceType::BlackKing.piece_index()].0,
+                &BLACK_PAWN_ATTACKS,
+            ),
+            Turn::BLACK => (
+                self.bitboards[PieceType::WhiteKing.piece_index()].0,
+                &WHITE_PAWN_ATTACKS,
+            ),
+        };
+        if enemy_king == 0 {
+            return;
         }
-        return legal_moves;
+
+        let king_square = enemy_king.trailing_zeros() as usize;
+        let occupied = self.occupied.0;
+        let empty = !occupied;
+
+        let bishop_checks = bishop_attacks(king_square, occupied);
+        let rook_checks = rook_attacks(king_square, occupied);
+        // Checking squares of each piece kind, indexed by `piece_index() % 6`
+        let checks = [
+            pawn_checks_table[king_square],
+            KNIGHTS_ATTACK_TABLE[king_square],
+            bishop_checks,
+            rook_checks,
+            bishop_checks | rook_checks,
+            0,
+        ];
+
+        let mut candidates = SmallVec::new();
+        self.generate_knight_moves(&mut candidates, empty & checks[1]);
+        // Queens move with both slider generators, so each gets every slider check
+        self.generate_bishop_moves_magics(&mut candidates, empty & checks[4]);
+        self.generate_rook_moves_magics(&mut candidates, empty & checks[4]);
+        self.generate_pawns_moves(&mut candidates, empty & checks[0] & !(RANK_1 | RANK_8));
+
+        moves.extend(
+            candidates
+                .into_iter()
+                .filter(|mv| checks[mv.piece().piece_index() % 6] & (1u64 << mv.to()) != 0),
+        );
+    } //
+
+    /// Whether the pseudo-legal move generator would produce `mv` in this position,
+    /// used for moves taken from other positions (TT moves, killers, countermoves).
+    pub fn is_pseudo_legal(&self, mv: Move) -> bool {
+        let (from, to) = (mv.from(), mv.to());
+        let to_bb = 1u64 << to;
+        let piece = mv.piece();
+        // Piece kind regardless of color, 0 for pawns up to 5 for kings
+        let kind = piece.piece_index() % 6;
+
+        let is_white_piece = piece.piece_index() < 6;
+        if self.piece_at[from] != Some(piece) || is_white_piece != (self.turn == Turn::WHITE) {
+            return false;
+        }
+        if self.get_allay_pieces().0 & to_bb != 0 {
+            return false;
+        }
+
+        if mv.is_castling() {
+            let mut moves = SmallVec::new();
+            self.generate_king_moves(&mut moves, !self.get_allay_pieces().0);
+            return moves.contains(&mv);
+        }
+
+        let pawn_attacks = match self.turn {
+            Turn::WHITE => WHITE_PAWN_ATTACKS[from],
+            Turn::BLACK => BLACK_PAWN_ATTACKS[from],
+        };
+
+        if mv.is_en_passant() {
+            let en_passant_rank = match self.turn {
+                Turn::WHITE => RANK_6,
+                Turn::BLACK => RANK_3,
+            };
+            return kind == 0
+                && self.en_passant == Some(to)
+                && to_bb & en_passant_rank != 0
+                && pawn_attacks & to_bb != 0
+                && mv.is_capture()
+                && !mv.is_promotion();
+        }
+
+        if mv.is_capture() != (self.get_enemy_pieces().0 & to_bb != 0) {
+            return false;
+        }
+
+        let occupied = self.occupied.0;
+        let reaches = match kind {
+            0 => {
+                let (push, start_rank, last_rank) = match self.turn {
+                    Turn::WHITE => (from + 8, RANK_2, RANK_8),
+                    Turn::BLACK => (from.wrapping_sub(8), RANK_7, RANK_1),
+                };
+                let double_push = match self.turn {
+                    Turn::WHITE => from + 16,
+                    Turn::BLACK => from.wrapping_sub(16),
+                };
+
+                if mv.is_promotion() != (to_bb & last_rank != 0) {
+                    return false;
+                }
+
+                if mv.is_capture() {
+                    pawn_attacks & to_bb != 0
+                } else if to == push {
+                    occupied & to_bb == 0
+                } else {
+                    to == double_push
+                        && (1u64 << from) & start_rank != 0
+                        && occupied & ((1u64 << push) | to_bb) == 0
+                }
+            }
+            1 => KNIGHTS_ATTACK_TABLE[from] & to_bb != 0,
+            2 => bishop_attacks(from, occupied) & to_bb != 0,
+            3 => rook_attacks(from, occupied) & to_bb != 0,
+            4 => (bishop_attacks(from, occupied) | rook_attacks(from, occupied)) & to_bb != 0,
+            _ => KING_ATTACK_TABLE[from] & to_bb != 0,
+        };
+
+        reaches && (kind == 0 || !mv.is_promotion())
+    } //
+
+    /// The move of `Move::compact()` in this position, if it is pseudo-legal.
+    pub fn move_from_compact(&self, compact: u16) -> Option<Move> {
+        let from = (compact & 0b111111) as usize;
+        let to = ((compact >> 6) & 0b111111) as usize;
+        let promotion = match compact >> 12 {
+            0 => None,
+            1 => Some(PieceType::WhiteKnight),
+            2 => Some(PieceType::WhiteBishop),
+            3 => Some(PieceType::WhiteRook),
+            4 => Some(PieceType::WhiteQueen),
+            _ => return None,
+        };
+
+        let piece = self.piece_at[from]?;
+        let en_passant =
+            piece.piece_index() % 6 == 0 && self.en_passant == Some(to) && from % 8 != to % 8;
+        let castling = piece.piece_index() % 6 == 5 && from.abs_diff(to) == 2;
+        let capture = self.piece_at[to].is_some() || en_passant;
+
+        let mv = Move::new(from, to, piece, capture, castling, promotion, en_passant);
+        self.is_pseudo_legal(mv).then_some(mv)
+    } //
+
+    pub fn generate_moves(&mut self) -> SmallVec<[Move; 256]> {
+        let mut moves = SmallVec::new();
+        self.generate_legal_moves(&mut moves);
+        moves
+    } //
+
+    /// Castling out of, through or into check, the part of castling legality
+    /// the pseudo-legal generator leaves to the caller.
+    pub fn is_castling_through_check(&self, mv: Move) -> bool {
+        let (from, to) = (mv.from(), mv.to());
+        let opposite_turn = self.opposite_turn();
+
+        (from.min(to)..=from.max(to)).any(|square| self.is_square_attacked(square as u8, opposite_turn))
     } //
 
     pub fn is_king_in_check(&self, turn: Turn) -> bool {
@@ -740,6 +939,8 @@ impl Board {
             eg_pst_eval: self.eg_pst_eval,
             mobility_eval: self.mobility_eval,
             last_irreversible_move: self.last_irreversible_move,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
             number_of_pawns: self.number_of_pawns,
             number_of_pieces: self.number_of_pieces,
             promotion_piece: mv.promotion_piece(),
@@ -796,6 +997,7 @@ impl Board {
         /* -----------------------------
             Clear en-passant by default
         ----------------------------- */
+        self.hash ^= self.en_passant_key();
         self.en_passant = None;
 
         if mv.is_castling() {
@@ -880,8 +1082,21 @@ impl Board {
             self.en_passant = Some(from - 8);
         }
 
+        /* -----------------------------
+            Update move clocks
+        ----------------------------- */
+        if capture.is_some() || piece == PieceType::WhitePawn || piece == PieceType::BlackPawn {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        if self.turn == Turn::BLACK {
+            self.fullmove_number += 1;
+        }
+
         // It Also Updates the hash
         self.switch_turn();
+        self.hash ^= self.en_passant_key();
 
         // Updating the hash for castling rights
         let diff = old_castling_rights ^ self.castling;
@@ -912,6 +1127,23 @@ impl Board {
         return undo;
     } //
 
+    /// Passes the turn without moving, returns the en passant square to restore.
+    pub fn make_null_move(&mut self) -> Option<usize> {
+        let en_passant = self.en_passant;
+
+        self.hash ^= self.en_passant_key();
+        self.en_passant = None;
+        self.switch_turn();
+
+        en_passant
+    } //
+
+    pub fn unmake_null_move(&mut self, en_passant: Option<usize>) {
+        self.switch_turn();
+        self.en_passant = en_passant;
+        self.hash ^= self.en_passant_key();
+    } //
+
     pub fn unmake_move(&mut self, unmake_move: UnMakeMove) {
         // self.bitboards = unmake_move.bitboards;
 
@@ -996,6 +1228,8 @@ impl Board {
         self.occupied = unmake_move.occupied;
         self.history.pop();
         self.last_irreversible_move = unmake_move.last_irreversible_move;
+        self.halfmove_clock = unmake_move.halfmove_clock;
+        self.fullmove_number = unmake_move.fullmove_number;
         self.number_of_pieces = unmake_move.number_of_pieces;
         self.number_of_pawns = unmake_move.number_of_pawns;
     } //
@@ -0,0 +1,216 @@
+use crate::board::history::{History, PreviousMoves};
+use crate::board::legal::Legality;
+use crate::board::pieces::PieceType;
+use crate::board::{Board, Move};
+use smallvec::SmallVec;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Stage {
+    TtMove,
+    GenerateCaptures,
+    GoodCaptures,
+    FirstKiller,
+    SecondKiller,
+    Countermove,
+    GenerateQuiets,
+    Quiets,
+    BadCaptures,
+    Done,
+}
+
+/// Moves of `Board::generate_captures`, searched before the quiet moves.
+#[inline(always)]
+fn is_noisy(mv: Move) -> bool {
+    mv.is_capture() || mv.promotion_piece() == Some(PieceType::WhiteQueen)
+}
+
+/// Hands out the pseudo-legal moves of a node, best first. When given the node's
+/// `Legality`, it hands out only the legal ones. Each stage starts its work only
+/// once the previous stages are exhausted, so a node that cuts off on the TT move
+/// never generates any moves.
+///
+/// Order: TT move, captures winning material by SEE (MVV-LVA), killers,
+/// countermove, quiet moves by history, then the losing captures.
+pub struct MovePicker {
+    stage: Stage,
+    tt_move: Option<Move>,
+    killers: [Option<Move>; 2],
+    countermove: Option<Move>,
+    /// Only legal moves are handed out when set
+    legality: Option<Legality>,
+    /// Quiescence search, only captures and queen promotions
+    noisy_only: bool,
+    skip_bad_captures: bool,
+    /// Scored moves of the current stage, the ones before `index` were handed out
+    moves: SmallVec<[(Move, i32); 64]>,
+    index: usize,
+    bad_captures: SmallVec<[Move; 32]>,
+} //
+
+impl MovePicker {
+    /// `tt_move` has to be pseudo-legal, killers and the countermove are checked here.
+    pub fn new(
+        tt_move: Option<Move>,
+        killers: [Option<Move>; 2],
+        countermove: Option<Move>,
+        legality: Option<Legality>,
+    ) -> Self {
+        MovePicker {
+            stage: Stage::TtMove,
+            tt_move,
+            killers,
+            countermove,
+            legality,
+            noisy_only: false,
+            skip_bad_captures: false,
+            moves: SmallVec::new(),
+            index: 0,
+            bad_captures: SmallVec::new(),
+        }
+    } //
+
+    /// Captures and queen promotions only, without the ones losing material by SEE when
+    /// `skip_bad_captures` is set.
+    pub fn quiescence(skip_bad_captures: bool, legality: Option<Legality>) -> Self {
+        MovePicker {
+            stage: Stage::GenerateCaptures,
+            noisy_only: true,
+            skip_bad_captures,
+            ..MovePicker::new(None, [None; 2], None, legality)
+        }
+    } //
+
+    pub fn next(
+        &mut self,
+        board: &Board,
+        history: &History,
+        previous: &PreviousMoves,
+    ) -> Option<Move> {
+        loop {
+            let mv = self.next_pseudo_legal(board, history, previous)?;
+
+            match self.legality {
+                Some(legality) if !legality.is_legal(board, mv) => (),
+                _ => return Some(mv),
+            }
+        }
+    } //
+
+    fn next_pseudo_legal(
+        &mut self,
+        board: &Board,
+        history: &History,
+        previous: &PreviousMoves,
+    ) -> Option<Move> {
+        loop {
+            match self.stage {
+                Stage::TtMove => {
+                    self.stage = Stage::GenerateCaptures;
+                    if self.tt_move.is_some() {
+                        return self.tt_move;
+                    }
+                }
+                Stage::GenerateCaptures => {
+                    let mut moves = SmallVec::new();
+                    board.generate_captures(&mut moves);
+
+                    for mv in moves {
+                        let promotion = mv.promotion_piece().map_or(0, |piece| piece.value());
+                        self.moves.push((mv, board.mvv_lva(mv) + promotion));
+                    }
+                    self.stage = Stage::GoodCaptures;
+                }
+                Stage::GoodCaptures => match self.pick() {
+                    Some(mv) if Some(mv) == self.tt_move => (),
+                    Some(mv) if !board.see_ge(mv, 0) => {
+                        if !self.skip_bad_captures {
+                            self.bad_captures.push(mv);
+                        }
+                    }
+                    Some(mv) => return Some(mv),
+                    None => {
+                        self.index = 0;
+                        self.stage = match self.noisy_only {
+                            true => Stage::BadCaptures,
+                            false => Stage::FirstKiller,
+                        };
+                    }
+                },
+                Stage::FirstKiller => {
+                    self.stage = Stage::SecondKiller;
+                    if let Some(mv) = self.killers[0]
+                        && self.is_quiet_candidate(board, mv)
+                    {
+                        return Some(mv);
+                    }
+                }
+                Stage::SecondKiller => {
+                    self.stage = Stage::Countermove;
+                    if let Some(mv) = self.killers[1]
+                        && Some(mv) != self.killers[0]
+                        && self.is_quiet_candidate(board, mv)
+                    {
+                        return Some(mv);
+                    }
+                }
+                Stage::Countermove => {
+                    self.stage = Stage::GenerateQuiets;
+                    if let Some(mv) = self.countermove
+                        && !self.killers.contains(&Some(mv))
+                        && self.is_quiet_candidate(board, mv)
+                    {
+                        return Some(mv);
+                    }
+                }
+                Stage::GenerateQuiets => {
+                    self.moves.clear();
+                    self.index = 0;
+
+                    let mut moves = SmallVec::new();
+                    board.generate_quiets(&mut moves);
+
+                    for mv in moves {
+                        let handed_out = Some(mv) == self.tt_move
+                            || self.killers.contains(&Some(mv))
+                            || Some(mv) == self.countermove;
+                        if !handed_out {
+                            self.moves.push((mv, history.quiet_score(mv, previous)));
+                        }
+                    }
+                    self.stage = Stage::Quiets;
+                }
+                Stage::Quiets => match self.pick() {
+                    Some(mv) => return Some(mv),
+                    None => {
+                        self.stage = Stage::BadCaptures;
+                        self.index = 0;
+                    }
+                },
+                Stage::BadCaptures => match self.bad_captures.get(self.index) {
+                    Some(&mv) => {
+                        self.index += 1;
+                        return Some(mv);
+                    }
+                    None => self.stage = Stage::Done,
+                },
+                Stage::Done => return None,
+            }
+        }
+    } //
+
+    /// Selection sort step, moves the best remaining move to `index` and hands it out.
+    fn pick(&mut self) -> Option<Move> {
+        let remaining = self.moves.get(self.index..)?;
+        let best = (0..remaining.len()).max_by_key(|&i| remaining[i].1)?;
+
+        self.moves.swap(self.index, self.index + best);
+        self.index += 1;
+        Some(self.moves[self.index - 1].0)
+    } //
+
+    /// A killer or countermove from another position, searched before the other
+    /// quiet moves when it is a pseudo-legal quiet move here.
+    fn is_quiet_candidate(&self, board: &Board, mv: Move) -> bool {
+        Some(mv) != self.tt_move && !is_noisy(mv) && board.is_pseudo_legal(mv)
+    } //
+} //
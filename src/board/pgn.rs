@@ -0,0 +1,596 @@
+use crate::board::{Board, FenError, Move, MoveParseError, Turn};
+use std::fmt;
+
+const SEVEN_TAG_ROSTER: [(&str, &str); 7] = [
+    ("Event", "?"),
+    ("Site", "?"),
+    ("Date", "????.??.??"),
+    ("Round", "?"),
+    ("White", "?"),
+    ("Black", "?"),
+    ("Result", "*"),
+];
+
+// Export format keeps movetext lines below 80 columns
+const MAX_LINE_LENGTH: usize = 79;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PgnMove {
+    pub mv: Move,
+    /// Comment placed before the move, only used at the start of a variation
+    pub comment_before: Option<String>,
+    pub nags: Vec<u8>,
+    pub comments: Vec<String>,
+    /// Alternative lines starting from the position before `mv`
+    pub variations: Vec<Vec<PgnMove>>,
+} //
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    /// Comment placed before the first move
+    pub comment: Option<String>,
+    pub moves: Vec<PgnMove>,
+} //
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PgnErrorKind {
+    MalformedTag,
+    UnterminatedComment,
+    UnexpectedToken(String),
+    UnbalancedVariation,
+    InvalidFen(FenError),
+    InvalidMove(MoveParseError),
+} //
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PgnError {
+    pub line: usize,
+    pub kind: PgnErrorKind,
+} //
+
+impl PgnError {
+    fn new(line: usize, kind: PgnErrorKind) -> PgnError {
+        PgnError { line, kind }
+    }
+} //
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PGN error on line {}: ", self.line)?;
+        match &self.kind {
+            PgnErrorKind::MalformedTag => write!(f, "malformed tag pair"),
+            PgnErrorKind::UnterminatedComment => write!(f, "unterminated comment"),
+            PgnErrorKind::UnexpectedToken(token) => write!(f, "unexpected token '{token}'"),
+            PgnErrorKind::UnbalancedVariation => write!(f, "unbalanced variation parentheses"),
+            PgnErrorKind::InvalidFen(err) => write!(f, "{err}"),
+            PgnErrorKind::InvalidMove(err) => write!(f, "{err}"),
+        }
+    }
+} //
+
+impl std::error::Error for PgnError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Tag(String, String),
+    Comment(String),
+    Nag(u8),
+    Open,
+    Close,
+    MoveNumber,
+    San(String),
+    Result(String),
+} //
+
+fn suffix_nag(suffix: &str) -> Option<u8> {
+    match suffix {
+        "!" => Some(1),
+        "?" => Some(2),
+        "!!" => Some(3),
+        "??" => Some(4),
+        "!?" => Some(5),
+        "?!" => Some(6),
+        _ => None,
+    }
+} //
+
+fn tokenize(pgn: &str) -> Result<Vec<(Token, usize)>, PgnError> {
+    let chars = pgn.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let at_line_start = i == 0 || chars[i - 1] == '\n';
+
+        match c {
+            '\n' => {
+                line += 1;
+                i += 1;
+            }
+            c if c.is_whitespace() => i += 1,
+            // Escape mechanism, the whole line is ignored
+            '%' if at_line_start => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            ';' => {
+                let start = i + 1;
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                let comment = chars[start..i].iter().collect::<String>();
+                tokens.push((Token::Comment(comment.trim().to_string()), line));
+            }
+            '{' => {
+                let start_line = line;
+                let start = i + 1;
+                while i < chars.len() && chars[i] != '}' {
+                    if chars[i] == '\n' {
+                        line += 1;
+                    }
+                    i += 1;
+                }
+                if i == chars.len() {
+                    return Err(PgnError::new(start_line, PgnErrorKind::UnterminatedComment));
+                }
+                let comment = chars[start..i].iter().collect::<String>();
+                let comment = comment.split_whitespace().collect::<Vec<_>>().join(" ");
+                tokens.push((Token::Comment(comment), start_line));
+                i += 1;
+            }
+            '[' => {
+                // Tag values may contain brackets, only stop outside of quotes
+                let mut end = i + 1;
+                let mut in_value = false;
+                while end < chars.len() && chars[end] != '\n' && (in_value || chars[end] != ']') {
+                    match chars[end] {
+                        '"' => in_value = !in_value,
+                        '\\' if in_value => end += 1,
+                        _ => (),
+                    }
+                    end += 1;
+                }
+                if end >= chars.len() || chars[end] != ']' {
+                    return Err(PgnError::new(line, PgnErrorKind::MalformedTag));
+                }
+                let tag = parse_tag(&chars[i + 1..end])
+                    .ok_or(PgnError::new(line, PgnErrorKind::MalformedTag))?;
+                tokens.push((tag, line));
+                i = end + 1;
+            }
+            '(' => {
+                tokens.push((Token::Open, line));
+                i += 1;
+            }
+            ')' => {
+                tokens.push((Token::Close, line));
+                i += 1;
+            }
+            '$' => {
+                let start = i + 1;
+                i += 1;
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+                let nag = chars[start..i].iter().collect::<String>();
+                let nag = nag.parse().map_err(|_| {
+                    PgnError::new(line, PgnErrorKind::UnexpectedToken(format!("${nag}")))
+                })?;
+                tokens.push((Token::Nag(nag), line));
+            }
+            _ => {
+                let start = i;
+                while i < chars.len()
+                    && !chars[i].is_whitespace()
+                    && !"{}()[];$".contains(chars[i])
+                {
+                    i += 1;
+                }
+                let word = chars[start..i].iter().collect::<String>();
+                push_word(&word, line, &mut tokens)?;
+            }
+        }
+    }
+
+    Ok(tokens)
+} //
+
+fn parse_tag(chars: &[char]) -> Option<Token> {
+    let inner = chars.iter().collect::<String>();
+    let inner = inner.trim();
+
+    let (name, value) = inner.split_once(char::is_whitespace)?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return None;
+    }
+
+    let mut unescaped = String::with_capacity(value.len());
+    let mut escaped = false;
+    for c in value.chars() {
+        if escaped || c != '\\' {
+            unescaped.push(c);
+            escaped = false;
+        } else {
+            escaped = true;
+        }
+    }
+
+    Some(Token::Tag(name.to_string(), unescaped))
+} //
+
+fn push_word(word: &str, line: usize, tokens: &mut Vec<(Token, usize)>) -> Result<(), PgnError> {
+    if matches!(word, "1-0" | "0-1" | "1/2-1/2" | "*") {
+        tokens.push((Token::Result(word.to_string()), line));
+        return Ok(());
+    }
+
+    // Move numbers may be glued to the move itself (`12.Nf3`, `12...Nf3`)
+    let digits = word.chars().take_while(|c| c.is_ascii_digit()).count();
+    let dots = word[digits..].chars().take_while(|&c| c == '.').count();
+    let san = if digits > 0 && dots > 0 {
+        tokens.push((Token::MoveNumber, line));
+        &word[digits + dots..]
+    } else if digits > 0 && digits == word.len() {
+        tokens.push((Token::MoveNumber, line));
+        ""
+    } else {
+        word
+    };
+
+    if san.is_empty() {
+        return Ok(());
+    }
+
+    let annotation_start = san.trim_end_matches(['!', '?']).len();
+    let (san, annotation) = san.split_at(annotation_start);
+
+    if san.is_empty() {
+        return Err(PgnError::new(line, PgnErrorKind::UnexpectedToken(word.to_string())));
+    }
+
+    tokens.push((Token::San(san.to_string()), line));
+    if let Some(nag) = suffix_nag(annotation) {
+        tokens.push((Token::Nag(nag), line));
+    }
+
+    Ok(())
+} //
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+} //
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or(self.tokens.last())
+            .map_or(1, |(_, line)| *line)
+    }
+
+    fn parse_game(&mut self) -> Result<PgnGame, PgnError> {
+        let mut game = PgnGame {
+            tags: Vec::new(),
+            comment: None,
+            moves: Vec::new(),
+        };
+
+        while let Some(Token::Tag(name, value)) = self.peek() {
+            game.set_tag(&name.clone(), &value.clone());
+            self.pos += 1;
+        }
+
+        let mut board = game
+            .start_board()
+            .map_err(|err| PgnError::new(self.line(), PgnErrorKind::InvalidFen(err)))?;
+
+        while let Some(Token::Comment(comment)) = self.peek() {
+            let comment = comment.clone();
+            match &mut game.comment {
+                Some(existing) => {
+                    existing.push(' ');
+                    existing.push_str(&comment);
+                }
+                None => game.comment = Some(comment),
+            }
+            self.pos += 1;
+        }
+
+        game.moves = self.parse_line(&mut board, false)?;
+
+        match self.peek() {
+            Some(Token::Result(result)) => {
+                let result = result.clone();
+                self.pos += 1;
+                if game.tag("Result").is_none_or(|tag| tag == "*") {
+                    game.set_tag("Result", &result);
+                }
+            }
+            Some(Token::Tag(..)) | None => (),
+            Some(_) => return Err(PgnError::new(self.line(), PgnErrorKind::UnbalancedVariation)),
+        }
+
+        Ok(game)
+    } //
+
+    fn parse_line(
+        &mut self,
+        board: &mut Board,
+        is_variation: bool,
+    ) -> Result<Vec<PgnMove>, PgnError> {
+        let mut moves: Vec<PgnMove> = Vec::new();
+        let mut last_undo = None;
+        let mut comment_before: Option<String> = None;
+
+        while let Some(token) = self.peek() {
+            let line = self.line();
+
+            match token.clone() {
+                Token::MoveNumber => self.pos += 1,
+                Token::San(san) => {
+                    let mv = Move::from_san(&san, board)
+                        .map_err(|err| PgnError::new(line, PgnErrorKind::InvalidMove(err)))?;
+                    last_undo = Some(board.make_move(mv));
+                    moves.push(PgnMove {
+                        mv,
+                        comment_before: comment_before.take(),
+                        nags: Vec::new(),
+                        comments: Vec::new(),
+                        variations: Vec::new(),
+                    });
+                    self.pos += 1;
+                }
+                Token::Nag(nag) => {
+                    let last = moves.last_mut().ok_or(PgnError::new(
+                        line,
+                        PgnErrorKind::UnexpectedToken(format!("${nag}")),
+                    ))?;
+                    last.nags.push(nag);
+                    self.pos += 1;
+                }
+                Token::Comment(comment) => {
+                    match (moves.last_mut(), &mut comment_before) {
+                        (Some(last), _) => last.comments.push(comment),
+                        (None, Some(existing)) => {
+                            existing.push(' ');
+                            existing.push_str(&comment);
+                        }
+                        // Comment at the start of a variation, keep it with its first move
+                        (None, None) => comment_before = Some(comment),
+                    }
+                    self.pos += 1;
+                }
+                Token::Open => {
+                    let (Some(last), Some(undo)) = (moves.last_mut(), last_undo) else {
+                        return Err(PgnError::new(line, PgnErrorKind::UnbalancedVariation));
+                    };
+                    self.pos += 1;
+
+                    // Variations replace the last move, so search from the position before it
+                    board.unmake_move(undo);
+                    let mut variation_board = board.clone();
+                    let variation = self.parse_line(&mut variation_board, true)?;
+
+                    if self.peek() != Some(&Token::Close) {
+                        return Err(PgnError::new(line, PgnErrorKind::UnbalancedVariation));
+                    }
+                    self.pos += 1;
+
+                    last.variations.push(variation);
+                    last_undo = Some(board.make_move(last.mv));
+                }
+                Token::Close if is_variation => break,
+                Token::Close => {
+                    return Err(PgnError::new(line, PgnErrorKind::UnbalancedVariation));
+                }
+                Token::Result(_) | Token::Tag(..) => break,
+            }
+        }
+
+        Ok(moves)
+    } //
+} //
+
+impl PgnGame {
+    /// Creates an empty game from the standard starting position.
+    pub fn new() -> PgnGame {
+        PgnGame {
+            tags: SEVEN_TAG_ROSTER
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            comment: None,
+            moves: Vec::new(),
+        }
+    } //
+
+    /// Creates an empty game starting from `board`, setting the `SetUp` and `FEN` tags.
+    pub fn from_board(board: &Board) -> PgnGame {
+        let mut game = PgnGame::new();
+        game.set_tag("SetUp", "1");
+        game.set_tag("FEN", &board.to_fen());
+        game
+    } //
+
+    /// Parses every game of a PGN database.
+    pub fn parse_all(pgn: &str) -> Result<Vec<PgnGame>, PgnError> {
+        let mut parser = Parser {
+            tokens: tokenize(pgn)?,
+            pos: 0,
+        };
+        let mut games = Vec::new();
+
+        while parser.peek().is_some() {
+            games.push(parser.parse_game()?);
+        }
+
+        Ok(games)
+    } //
+
+    /// Parses the first game of a PGN text.
+    pub fn parse(pgn: &str) -> Result<PgnGame, PgnError> {
+        let mut games = PgnGame::parse_all(pgn)?;
+        if games.is_empty() {
+            return Ok(PgnGame::new());
+        }
+        Ok(games.swap_remove(0))
+    } //
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    } //
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, existing)) => *existing = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    } //
+
+    pub fn push_move(&mut self, mv: Move) {
+        self.moves.push(PgnMove {
+            mv,
+            comment_before: None,
+            nags: Vec::new(),
+            comments: Vec::new(),
+            variations: Vec::new(),
+        });
+    } //
+
+    /// Position the game starts from, taken from the `FEN` tag when present.
+    pub fn start_board(&self) -> Result<Board, FenError> {
+        match self.tag("FEN") {
+            Some(fen) => Board::from_fen(fen),
+            None => Ok(Board::new()),
+        }
+    } //
+
+    /// Position reached at the end of the mainline.
+    pub fn final_board(&self) -> Result<Board, FenError> {
+        let mut board = self.start_board()?;
+        for pgn_move in &self.moves {
+            board.make_move(pgn_move.mv);
+        }
+        Ok(board)
+    } //
+
+    /// Writes the game in PGN export format, the Seven Tag Roster comes first.
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = String::new();
+
+        for (name, default) in SEVEN_TAG_ROSTER {
+            let value = self.tag(name).unwrap_or(default);
+            pgn.push_str(&format!("[{name} \"{}\"]\n", escape_tag(value)));
+        }
+        for (name, value) in &self.tags {
+            if SEVEN_TAG_ROSTER.iter().all(|(roster, _)| roster != name) {
+                pgn.push_str(&format!("[{name} \"{}\"]\n", escape_tag(value)));
+            }
+        }
+        pgn.push('\n');
+
+        let mut tokens = Vec::new();
+        if let Some(comment) = &self.comment {
+            push_comment(comment, &mut tokens);
+        }
+        if let Ok(board) = self.start_board() {
+            write_line(&board, &self.moves, &mut tokens);
+        }
+        tokens.push(self.tag("Result").unwrap_or("*").to_string());
+
+        let mut line_length = 0;
+        for token in tokens {
+            if line_length > 0 && line_length + 1 + token.len() > MAX_LINE_LENGTH {
+                pgn.push('\n');
+                line_length = 0;
+            } else if line_length > 0 {
+                pgn.push(' ');
+                line_length += 1;
+            }
+            pgn.push_str(&token);
+            line_length += token.len();
+        }
+        pgn.push_str("\n\n");
+
+        pgn
+    } //
+} //
+
+impl Default for PgnGame {
+    fn default() -> Self {
+        PgnGame::new()
+    }
+} //
+
+fn escape_tag(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+} //
+
+/// Comments are split into words so that long comments wrap like the rest of the movetext.
+fn push_comment(comment: &str, tokens: &mut Vec<String>) {
+    let words = comment.replace('}', "").split_whitespace().map(str::to_string).collect::<Vec<_>>();
+
+    match words.len() {
+        0 => tokens.push("{}".to_string()),
+        len => {
+            for (index, word) in words.into_iter().enumerate() {
+                let open = if index == 0 { "{" } else { "" };
+                let close = if index == len - 1 { "}" } else { "" };
+                tokens.push(format!("{open}{word}{close}"));
+            }
+        }
+    }
+} //
+
+fn write_line(board: &Board, moves: &[PgnMove], tokens: &mut Vec<String>) {
+    let mut board = board.clone();
+    let mut needs_number = true;
+
+    for pgn_move in moves {
+        if let Some(comment) = &pgn_move.comment_before {
+            push_comment(comment, tokens);
+            needs_number = true;
+        }
+
+        match board.turn {
+            Turn::WHITE => tokens.push(format!("{}.", board.fullmove_number)),
+            Turn::BLACK if needs_number => tokens.push(format!("{}...", board.fullmove_number)),
+            Turn::BLACK => (),
+        }
+
+        tokens.push(pgn_move.mv.to_san(&board));
+        for nag in &pgn_move.nags {
+            tokens.push(format!("${nag}"));
+        }
+        for comment in &pgn_move.comments {
+            push_comment(comment, tokens);
+        }
+        for variation in &pgn_move.variations {
+            let start = tokens.len();
+            write_line(&board, variation, tokens);
+            if tokens.len() > start {
+                tokens[start].insert(0, '(');
+                if let Some(last) = tokens.last_mut() {
+                    last.push(')');
+                }
+            }
+        }
+
+        needs_number = !pgn_move.comments.is_empty() || !pgn_move.variations.is_empty();
+        board.make_move(pgn_move.mv);
+    }
+} //
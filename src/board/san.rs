@@ -0,0 +1,179 @@
+use crate::board::pieces::PieceType;
+use crate::board::{Board, Move, MoveParseError, parse_square, square_name};
+
+fn piece_letter(piece: PieceType) -> Option<char> {
+    match piece.piece_index() % 6 {
+        1 => Some('N'),
+        2 => Some('B'),
+        3 => Some('R'),
+        4 => Some('Q'),
+        5 => Some('K'),
+        _ => None,
+    }
+} //
+
+impl Move {
+    /// Formats the move in Standard Algebraic Notation, `board` is the position
+    /// before the move is played.
+    pub fn to_san(self, board: &Board) -> String {
+        let mut board = board.clone();
+        let legal_moves = board.generate_moves();
+
+        let mut san = String::with_capacity(8);
+
+        if self.is_castling() {
+            san.push_str(if self.to() % 8 == 6 { "O-O" } else { "O-O-O" });
+        } else if let Some(letter) = piece_letter(self.piece()) {
+            san.push(letter);
+
+            // Other pieces of the same kind that can reach the same square
+            let rivals = legal_moves
+                .iter()
+                .filter(|mv| {
+                    mv.piece() == self.piece() && mv.to() == self.to() && mv.from() != self.from()
+                })
+                .collect::<Vec<_>>();
+
+            if !rivals.is_empty() {
+                let same_file = rivals.iter().any(|mv| mv.from() % 8 == self.from() % 8);
+                let same_rank = rivals.iter().any(|mv| mv.from() / 8 == self.from() / 8);
+                let from = square_name(self.from());
+
+                if !same_file {
+                    san.push_str(&from[0..1]);
+                } else if !same_rank {
+                    san.push_str(&from[1..2]);
+                } else {
+                    san.push_str(&from);
+                }
+            }
+
+            if self.is_capture() {
+                san.push('x');
+            }
+            san.push_str(&square_name(self.to()));
+        } else {
+            if self.is_capture() {
+                san.push((b'a' + (self.from() % 8) as u8) as char);
+                san.push('x');
+            }
+            san.push_str(&square_name(self.to()));
+
+            if let Some(promotion) = self.promotion_piece() {
+                san.push('=');
+                san.push(piece_letter(promotion).unwrap_or('Q'));
+            }
+        }
+
+        board.make_move(self);
+        if board.is_king_in_check(board.turn) {
+            san.push(if board.generate_moves().is_empty() { '#' } else { '+' });
+        }
+
+        san
+    } //
+
+    /// Parses a move in Standard Algebraic Notation and matches it against the legal
+    /// moves of `board`.
+    ///
+    /// Relaxed forms are accepted: `0-0` castling, missing or extra capture marks,
+    /// long forms such as `Ng1f3`, `e2-e4` or `e7e8q`, promotions without `=` and
+    /// trailing annotations (`+`, `#`, `!`, `?`, `e.p.`).
+    pub fn from_san(san: &str, board: &Board) -> Result<Move, MoveParseError> {
+        let invalid = || MoveParseError::InvalidSan(san.to_string());
+
+        let text = san.trim();
+        let text = text.strip_suffix("e.p.").unwrap_or(text).trim_end();
+        let text = text.trim_end_matches(['+', '#', '!', '?']);
+
+        let mut board = board.clone();
+        let legal_moves = board.generate_moves();
+
+        if let Some(long) = match text {
+            "O-O" | "0-0" => Some(false),
+            "O-O-O" | "0-0-0" => Some(true),
+            _ => None,
+        } {
+            return legal_moves
+                .iter()
+                .find(|mv| mv.is_castling() && (mv.to() % 8 == 2) == long)
+                .copied()
+                .ok_or_else(|| MoveParseError::Illegal(san.to_string()));
+        }
+
+        let mut chars = text
+            .chars()
+            .filter(|c| !matches!(c, 'x' | 'X' | ':' | '-'))
+            .collect::<Vec<_>>();
+
+        let piece_kind = match chars.first() {
+            Some('N') => Some(1),
+            Some('B') => Some(2),
+            Some('R') => Some(3),
+            Some('Q') => Some(4),
+            Some('K') => Some(5),
+            Some(_) => None,
+            None => return Err(invalid()),
+        };
+        if piece_kind.is_some() {
+            chars.remove(0);
+        }
+
+        // Anything after the destination square is the promotion piece
+        let promotion = match chars.last() {
+            Some(c) if c.is_ascii_alphabetic() => {
+                let promotion = match c.to_ascii_uppercase() {
+                    'Q' => PieceType::WhiteQueen,
+                    'R' => PieceType::WhiteRook,
+                    'B' => PieceType::WhiteBishop,
+                    'N' => PieceType::WhiteKnight,
+                    _ => return Err(MoveParseError::InvalidPromotion(*c)),
+                };
+                chars.pop();
+                if chars.last() == Some(&'=') {
+                    chars.pop();
+                }
+                Some(promotion)
+            }
+            _ => None,
+        };
+
+        if chars.len() < 2 || chars.len() > 4 {
+            return Err(invalid());
+        }
+
+        let (hint, target) = chars.split_at(chars.len() - 2);
+        let target = target.iter().collect::<String>();
+        let to = parse_square(target.as_bytes()).ok_or_else(invalid)?;
+
+        let mut from_file = None;
+        let mut from_rank = None;
+        for c in hint {
+            match c {
+                'a'..='h' if from_file.is_none() && from_rank.is_none() => {
+                    from_file = Some(*c as usize - 'a' as usize)
+                }
+                '1'..='8' if from_rank.is_none() => from_rank = Some(*c as usize - '1' as usize),
+                _ => return Err(invalid()),
+            }
+        }
+
+        // A full origin square without a piece letter is the long form (`g1f3`)
+        let any_piece = piece_kind.is_none() && from_file.is_some() && from_rank.is_some();
+        let piece_kind = piece_kind.unwrap_or(0);
+
+        let mut candidates = legal_moves.iter().filter(|mv| {
+            (any_piece || mv.piece().piece_index() % 6 == piece_kind)
+                && mv.to() == to
+                && (promotion.is_none() || mv.promotion_piece() == promotion)
+                && from_file.is_none_or(|file| mv.from() % 8 == file)
+                && from_rank.is_none_or(|rank| mv.from() / 8 == rank)
+        });
+
+        match (candidates.next(), candidates.next()) {
+            (Some(mv), None) => Ok(*mv),
+            (Some(_), Some(_)) => Err(MoveParseError::Ambiguous(san.to_string())),
+            (None, _) => Err(MoveParseError::Illegal(san.to_string())),
+        }
+    } //
+} //
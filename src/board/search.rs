@@ -0,0 +1,230 @@
+use crate::board::engine::StopHandle;
+use crate::board::{Move, Turn};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Score of the side to move when it is checkmated, mates found further from
+/// the root score `MATE_SCORE - ply`.
+pub const MATE_SCORE: i32 = 30_000;
+/// Any score beyond this is a mate score.
+pub const MATE_BOUND: i32 = 29_000;
+
+/// Search feature toggles and tunable constants.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SearchConfig {
+    pub alpha_beta: bool,
+    pub tt: bool,
+    pub null_move_pruning: bool,
+    pub lmr: bool,
+    pub quiescence: bool,
+    /// Principal variation search, null window searches for all but the first move
+    pub pvs: bool,
+    /// Search each iteration within `aspiration_window` of the previous score
+    pub aspiration_windows: bool,
+    /// Skip captures losing material in quiescence, and quiet moves losing more than
+    /// `see_quiet_margin` per ply of remaining depth near the horizon
+    pub see_pruning: bool,
+    /// Generate only legal moves from pin and check masks, instead of making every
+    /// pseudo-legal move to see whether it leaves the king in check
+    pub legal_move_gen: bool,
+    /// Reorder the root moves by their score from the previous iteration
+    pub move_ordering: bool,
+    /// Play from the opening book when the position is in it
+    pub own_book: bool,
+    /// Print a UCI `info` line to stdout after every completed iteration
+    pub print_info: bool,
+
+    /// Depth reduction `R` of the null move search
+    pub null_move_reduction: i8,
+    pub null_move_min_depth: i8,
+    /// Moves searched at full depth before late move reductions kick in
+    pub lmr_min_move_index: usize,
+    pub lmr_min_depth: i8,
+    pub lmr_reduction: i8,
+    /// Delta pruning margin of the quiescence search
+    pub quiescence_margin: i32,
+    /// Initial half width of the aspiration window, doubled on every fail
+    pub aspiration_window: i32,
+    pub see_quiet_max_depth: i8,
+    pub see_quiet_margin: i32,
+} //
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        SearchConfig {
+            alpha_beta: true,
+            tt: true,
+            null_move_pruning: true,
+            lmr: true,
+            quiescence: true,
+            pvs: true,
+            aspiration_windows: true,
+            see_pruning: true,
+            legal_move_gen: true,
+            move_ordering: true,
+            own_book: true,
+            print_info: true,
+            null_move_reduction: 2,
+            null_move_min_depth: 3,
+            lmr_min_move_index: 4,
+            lmr_min_depth: 3,
+            lmr_reduction: 1,
+            quiescence_margin: 900, // queen value
+            aspiration_window: 50,
+            see_quiet_max_depth: 3,
+            see_quiet_margin: 50,
+        }
+    }
+} //
+
+/// When to stop searching, mirrors the arguments of the UCI `go` command.
+/// Every limit is optional, an empty `SearchLimits` searches up to `MAX_DEPTH`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SearchLimits {
+    pub depth: Option<i32>,
+    pub nodes: Option<u64>,
+    pub movetime: Option<Duration>,
+    pub wtime: Option<Duration>,
+    pub btime: Option<Duration>,
+    pub winc: Option<Duration>,
+    pub binc: Option<Duration>,
+    pub movestogo: Option<u32>,
+    /// Search for a mate in this many moves
+    pub mate: Option<u32>,
+    pub infinite: bool,
+    /// Search on the opponent's time, limits only apply after a ponderhit
+    pub ponder: bool,
+} //
+
+impl SearchLimits {
+    pub const MAX_DEPTH: i32 = 64;
+
+    pub fn depth(depth: i32) -> Self {
+        SearchLimits {
+            depth: Some(depth),
+            ..Default::default()
+        }
+    } //
+
+    pub fn nodes(nodes: u64) -> Self {
+        SearchLimits {
+            nodes: Some(nodes),
+            ..Default::default()
+        }
+    } //
+
+    pub fn movetime(movetime: Duration) -> Self {
+        SearchLimits {
+            movetime: Some(movetime),
+            ..Default::default()
+        }
+    } //
+
+    pub fn infinite() -> Self {
+        SearchLimits {
+            infinite: true,
+            ..Default::default()
+        }
+    } //
+
+    /// Deepest iteration allowed, never beyond `MAX_DEPTH`. A mate in `n` is
+    /// `2n - 1` plies away, one more ply is needed to find the mated side without
+    /// legal moves.
+    pub fn max_depth(&self) -> i32 {
+        let depth = self.depth.unwrap_or(Self::MAX_DEPTH).min(Self::MAX_DEPTH);
+
+        match self.mate {
+            Some(mate) => {
+                let mate_depth = mate.min(Self::MAX_DEPTH as u32) as i32 * 2;
+                depth.min(mate_depth).max(1)
+            }
+            None => depth,
+        }
+    } //
+
+    pub fn max_nodes(&self) -> u64 {
+        self.nodes.unwrap_or(u64::MAX)
+    } //
+
+    /// Time the side to move may spend on this move, `None` when the search is
+    /// not bounded by time.
+    pub fn time_budget(&self, turn: Turn) -> Option<Duration> {
+        if self.infinite {
+            return None;
+        }
+        if let Some(movetime) = self.movetime {
+            return Some(movetime);
+        }
+
+        let (time, increment) = match turn {
+            Turn::WHITE => (self.wtime?, self.winc.unwrap_or_default()),
+            Turn::BLACK => (self.btime?, self.binc.unwrap_or_default()),
+        };
+
+        // Without movestogo assume a long game, and never plan to use more than half the clock
+        let moves_to_go = self.movestogo.unwrap_or(100).max(1);
+        let budget = time / moves_to_go + increment / 2;
+
+        Some(budget.min(time / 2))
+    } //
+} //
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SearchResult {
+    /// `None` when the side to move is checkmated or stalemated
+    pub best_move: Option<Move>,
+    /// Expected reply to `best_move`
+    pub ponder_move: Option<Move>,
+    /// From the side to move's point of view
+    pub score: i32,
+    /// Last completed iteration, 0 for book moves
+    pub depth: i32,
+    pub pv: Vec<Move>,
+    pub nodes: u64,
+} //
+
+/// A search running on its own thread, returned by `Engine::start_search`.
+#[derive(Debug)]
+pub struct SearchHandle {
+    thread: JoinHandle<SearchResult>,
+    stop: StopHandle,
+    pondering: Arc<AtomicBool>,
+} //
+
+impl SearchHandle {
+    pub(crate) fn new(
+        thread: JoinHandle<SearchResult>,
+        stop: StopHandle,
+        pondering: Arc<AtomicBool>,
+    ) -> Self {
+        SearchHandle {
+            thread,
+            stop,
+            pondering,
+        }
+    } //
+
+    /// Asks the search to finish as soon as possible, `join` still has to be called
+    /// to get the result.
+    pub fn stop(&self) {
+        self.stop.stop();
+    } //
+
+    /// The opponent played the expected move, the search switches from pondering
+    /// to its regular limits.
+    pub fn ponderhit(&self) {
+        self.pondering.store(false, Ordering::Relaxed);
+    } //
+
+    pub fn is_running(&self) -> bool {
+        !self.thread.is_finished()
+    } //
+
+    /// Waits for the search to finish. Infinite and pondering searches only finish
+    /// after `stop` (or `ponderhit` for the latter).
+    pub fn join(self) -> SearchResult {
+        self.thread.join().expect("search thread panicked")
+    } //
+} //
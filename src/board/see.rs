@@ -0,0 +1,153 @@
+use crate::board::bishop_magic::bishop_attacks;
+use crate::board::constants::{
+    BLACK_PAWN_ATTACKS, KING_ATTACK_TABLE, KNIGHTS_ATTACK_TABLE, WHITE_PAWN_ATTACKS,
+};
+use crate::board::pieces::PieceType;
+use crate::board::rook_magic::rook_attacks;
+use crate::board::{Board, Move, Turn};
+
+/// Piece values of the exchange, indexed by `piece_index() % 6`. The king is
+/// never captured, it only recaptures when nothing defends the square anymore.
+const SEE_VALUE: [i32; 6] = [100, 300, 300, 500, 900, 0];
+
+#[inline(always)]
+fn see_value(piece: PieceType) -> i32 {
+    SEE_VALUE[piece.piece_index() % 6]
+} //
+
+impl Board {
+    /// Pieces of both sides attacking `square` when only the pieces in `occupied`
+    /// are on the board.
+    pub fn attackers_to(&self, square: usize, occupied: u64) -> u64 {
+        let bbs = &self.bitboards;
+        let bishops_queens = bbs[2].0 | bbs[4].0 | bbs[8].0 | bbs[10].0;
+        let rooks_queens = bbs[3].0 | bbs[4].0 | bbs[9].0 | bbs[10].0;
+
+        (BLACK_PAWN_ATTACKS[square] & bbs[0].0)
+            | (WHITE_PAWN_ATTACKS[square] & bbs[6].0)
+            | (KNIGHTS_ATTACK_TABLE[square] & (bbs[1].0 | bbs[7].0))
+            | (KING_ATTACK_TABLE[square] & (bbs[5].0 | bbs[11].0))
+            | (bishop_attacks(square, occupied) & bishops_queens)
+            | (rook_attacks(square, occupied) & rooks_queens)
+    } //
+
+    fn side_pieces(&self, turn: Turn) -> u64 {
+        let bbs = match turn {
+            Turn::WHITE => &self.bitboards[0..6],
+            Turn::BLACK => &self.bitboards[6..12],
+        };
+        bbs.iter().fold(0, |pieces, bb| pieces | bb.0)
+    } //
+
+    /// Least valuable of `turn`'s pieces in `attackers`.
+    fn least_valuable_attacker(&self, attackers: u64, turn: Turn) -> Option<(PieceType, u64)> {
+        let first = match turn {
+            Turn::WHITE => 0,
+            Turn::BLACK => 6,
+        };
+
+        (first..first + 6).find_map(|index| {
+            let pieces = attackers & self.bitboards[index].0;
+            if pieces == 0 {
+                return None;
+            }
+            let square = pieces.trailing_zeros() as usize;
+            Some((self.piece_at[square]?, 1u64 << square))
+        })
+    } //
+
+    /// Static exchange evaluation: material won by the side to move when both sides
+    /// keep recapturing on the target square of `mv` with their least valuable
+    /// piece, and either side may stop when recapturing would lose material.
+    /// Sliders behind the capturing pieces join the exchange, pins are ignored.
+    pub fn see(&self, mv: Move) -> i32 {
+        if mv.is_castling() {
+            return 0;
+        }
+
+        let (from, to) = (mv.from(), mv.to());
+        let mut occupied = self.occupied.0 ^ (1u64 << from);
+
+        // Material won by the move itself
+        let mut gain = [0; 32];
+        gain[0] = match self.piece_at[to] {
+            Some(captured) => see_value(captured),
+            None if mv.is_en_passant() => {
+                occupied ^= 1u64 << (to ^ 8);
+                SEE_VALUE[0]
+            }
+            None => 0,
+        };
+        let mut on_square = see_value(mv.piece());
+        if let Some(promotion) = mv.promotion_piece() {
+            gain[0] += see_value(promotion) - SEE_VALUE[0];
+            on_square = see_value(promotion);
+        }
+
+        let mut attackers = self.attackers_to(to, occupied) & occupied;
+        let mut turn = self.opposite_turn();
+        let mut depth = 0;
+
+        while let Some((piece, attacker)) = self.least_valuable_attacker(attackers, turn) {
+            occupied ^= attacker;
+            // Sliders lined up behind the attacker
+            attackers |= self.attackers_to(to, occupied);
+            attackers &= occupied;
+
+            // The king can't recapture into a defended square
+            let is_king = piece.piece_index() % 6 == 5;
+            if is_king && attackers & self.side_pieces(opposite(turn)) != 0 {
+                break;
+            }
+
+            depth += 1;
+            gain[depth] = on_square - gain[depth - 1];
+            on_square = see_value(piece);
+            turn = opposite(turn);
+
+            if depth == gain.len() - 1 {
+                break;
+            }
+        }
+
+        // Either side stops once recapturing doesn't pay anymore
+        while depth > 0 {
+            gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+            depth -= 1;
+        }
+
+        gain[0]
+    } //
+
+    /// `see(mv) >= threshold`, without the exchange when the first capture already
+    /// settles it.
+    pub fn see_ge(&self, mv: Move, threshold: i32) -> bool {
+        let captured = match self.piece_at[mv.to()] {
+            Some(captured) => see_value(captured),
+            None if mv.is_en_passant() => SEE_VALUE[0],
+            None => 0,
+        };
+        let promotion = mv
+            .promotion_piece()
+            .map_or(0, |promotion| see_value(promotion) - SEE_VALUE[0]);
+
+        // Even unopposed the move doesn't reach the threshold
+        if captured + promotion < threshold {
+            return false;
+        }
+        // Still enough after losing the moving piece
+        if captured - see_value(mv.piece()) >= threshold {
+            return true;
+        }
+
+        self.see(mv) >= threshold
+    } //
+} //
+
+#[inline(always)]
+fn opposite(turn: Turn) -> Turn {
+    match turn {
+        Turn::WHITE => Turn::BLACK,
+        Turn::BLACK => Turn::WHITE,
+    }
+} //
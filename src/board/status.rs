@@ -0,0 +1,129 @@
+use crate::board::pieces::PieceType;
+use crate::board::Board;
+use std::fmt;
+
+const LIGHT_SQUARES: u64 = 0x55AA55AA55AA55AA;
+const DARK_SQUARES: u64 = !LIGHT_SQUARES;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GameStatus {
+    Ongoing,
+    /// The side to move has been checkmated
+    Checkmate,
+    Stalemate,
+    /// Claimable draw
+    ThreefoldRepetition,
+    FivefoldRepetition,
+    /// Claimable draw
+    FiftyMoveRule,
+    SeventyFiveMoveRule,
+    /// Neither side can possibly deliver mate
+    InsufficientMaterial,
+} //
+
+impl GameStatus {
+    /// Claimable draws do not end the game on their own.
+    pub fn is_game_over(self) -> bool {
+        !matches!(
+            self,
+            GameStatus::Ongoing | GameStatus::ThreefoldRepetition | GameStatus::FiftyMoveRule
+        )
+    }
+
+    pub fn is_draw(self) -> bool {
+        !matches!(self, GameStatus::Ongoing | GameStatus::Checkmate)
+    }
+
+    pub fn has_legal_moves(self) -> bool {
+        !matches!(self, GameStatus::Checkmate | GameStatus::Stalemate)
+    }
+} //
+
+impl fmt::Display for GameStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            GameStatus::Ongoing => "ongoing",
+            GameStatus::Checkmate => "checkmate",
+            GameStatus::Stalemate => "stalemate",
+            GameStatus::ThreefoldRepetition => "threefold repetition",
+            GameStatus::FivefoldRepetition => "fivefold repetition",
+            GameStatus::FiftyMoveRule => "fifty-move rule",
+            GameStatus::SeventyFiveMoveRule => "seventy-five-move rule",
+            GameStatus::InsufficientMaterial => "insufficient material",
+        };
+        f.write_str(name)
+    }
+} //
+
+impl Board {
+    /// Full game termination check, mate and stalemate take precedence over every draw rule.
+    pub fn game_status(&mut self) -> GameStatus {
+        if self.generate_moves().is_empty() {
+            if self.is_king_in_check(self.turn) {
+                return GameStatus::Checkmate;
+            }
+            return GameStatus::Stalemate;
+        }
+
+        let repetitions = self.repetition_count();
+
+        if self.is_insufficient_material() {
+            GameStatus::InsufficientMaterial
+        } else if repetitions >= 5 {
+            GameStatus::FivefoldRepetition
+        } else if self.halfmove_clock >= 150 {
+            GameStatus::SeventyFiveMoveRule
+        } else if repetitions >= 3 {
+            GameStatus::ThreefoldRepetition
+        } else if self.halfmove_clock >= 100 {
+            GameStatus::FiftyMoveRule
+        } else {
+            GameStatus::Ongoing
+        }
+    } //
+
+    /// Draws the search stops at: threefold repetition, the fifty-move rule and
+    /// insufficient material. Moves are only generated once the halfmove clock
+    /// reaches 100, to tell a mate on the last move from a draw.
+    #[inline(always)]
+    pub fn is_draw(&mut self) -> bool {
+        self.is_3fold_repetition() || self.is_fifty_move_draw() || self.is_insufficient_material()
+    } //
+
+    /// Number of times the current position occurred since the last irreversible move,
+    /// including the current occurrence.
+    pub fn repetition_count(&self) -> usize {
+        self.history[self.last_irreversible_move..]
+            .iter()
+            .filter(|&&hash| hash == self.hash)
+            .count()
+    } //
+
+    /// K vs K, KB vs K, KN vs K and positions where every remaining piece besides
+    /// the kings is a bishop standing on the same square color.
+    pub fn is_insufficient_material(&self) -> bool {
+        let heavy_or_pawns = self.bitboards[PieceType::WhitePawn.piece_index()].0
+            | self.bitboards[PieceType::BlackPawn.piece_index()].0
+            | self.bitboards[PieceType::WhiteRook.piece_index()].0
+            | self.bitboards[PieceType::BlackRook.piece_index()].0
+            | self.bitboards[PieceType::WhiteQueen.piece_index()].0
+            | self.bitboards[PieceType::BlackQueen.piece_index()].0;
+
+        if heavy_or_pawns != 0 {
+            return false;
+        }
+
+        let knights = self.bitboards[PieceType::WhiteKnight.piece_index()].0
+            | self.bitboards[PieceType::BlackKnight.piece_index()].0;
+        let bishops = self.bitboards[PieceType::WhiteBishop.piece_index()].0
+            | self.bitboards[PieceType::BlackBishop.piece_index()].0;
+
+        let minors = (knights | bishops).count_ones();
+
+        if minors <= 1 {
+            return true;
+        }
+
+        knights == 0 && (bishops & LIGHT_SQUARES == 0 || bishops & DARK_SQUARES == 0)
+    } //
+} //
@@ -1,4 +1,6 @@
 use crate::board::Move;
+use crate::board::search::MATE_BOUND;
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
 
 #[repr(u8)]
 #[derive(Copy, Clone, PartialEq, Eq)]
@@ -8,95 +10,237 @@ pub enum Bound {
     Upper = 2,
 }
 
+impl Bound {
+    /// Bound of a search result within `(alpha, beta)`. Decided on the score relative
+    /// to the root, the same window `alpha` and `beta` are in.
+    #[inline(always)]
+    pub fn new(score: i32, alpha: i32, beta: i32, all_searched: bool) -> Bound {
+        if score <= alpha {
+            Bound::Upper
+        } else if score >= beta || !all_searched {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        }
+    } //
+} //
+
 #[derive(Copy, Clone)]
 pub struct TTEntry {
-    pub key: u64,  // full zobrist
+    pub key: u16,  // low bits of the zobrist key, the rest selects the bucket
     pub depth: i8, // remaining depth
     pub bound: Bound,
-    pub score: i32, // normalized score
-    pub best_move: Move,
+    pub score: i32,     // normalized score
+    pub best_move: u16, // Move::compact()
+    pub generation: u8,
+}
+
+// Packed entry layout: key (16 bits) | move (16) | score (16) | depth (8) | bound (2) | generation (6)
+const MOVE_SHIFT: u32 = 16;
+const SCORE_SHIFT: u32 = 32;
+const DEPTH_SHIFT: u32 = 48;
+const BOUND_SHIFT: u32 = 56;
+const GENERATION_SHIFT: u32 = 58;
+const GENERATION_MASK: u8 = 0b111111;
+
+impl TTEntry {
+    #[inline(always)]
+    fn pack(self) -> u64 {
+        self.key as u64
+            | (self.best_move as u64) << MOVE_SHIFT
+            | (self.score as i16 as u16 as u64) << SCORE_SHIFT
+            | (self.depth as u8 as u64) << DEPTH_SHIFT
+            | (self.bound as u64) << BOUND_SHIFT
+            | (self.generation as u64) << GENERATION_SHIFT
+    } //
+
+    #[inline(always)]
+    fn unpack(data: u64) -> TTEntry {
+        TTEntry {
+            key: data as u16,
+            best_move: (data >> MOVE_SHIFT) as u16,
+            score: (data >> SCORE_SHIFT) as u16 as i16 as i32,
+            depth: (data >> DEPTH_SHIFT) as u8 as i8,
+            bound: match (data >> BOUND_SHIFT) & 0b11 {
+                0 => Bound::Exact,
+                1 => Bound::Lower,
+                _ => Bound::Upper,
+            },
+            generation: (data >> GENERATION_SHIFT) as u8,
+        }
+    } //
+
+    /// Score to return at once for a node searched to `depth` within `(alpha, beta)`
+    /// at `ply`: exact scores, lower bounds at or above `beta` and upper bounds at
+    /// or below `alpha`, when the entry was searched at least as deep.
+    #[inline(always)]
+    pub fn cutoff(self, depth: i8, alpha: i32, beta: i32, ply: usize) -> Option<i32> {
+        if self.depth < depth {
+            return None;
+        }
+        let score = score_from_tt(self.score, ply);
+
+        match self.bound {
+            Bound::Exact => Some(score),
+            Bound::Lower if score >= beta => Some(score),
+            Bound::Upper if score <= alpha => Some(score),
+            _ => None,
+        }
+    } //
+
+    /// Searches since the entry was written.
+    #[inline(always)]
+    fn age(self, generation: u8) -> i32 {
+        (generation.wrapping_sub(self.generation) & GENERATION_MASK) as i32
+    } //
+} //
+
+/// Mate scores are stored relative to the node instead of the root, so the
+/// entry stays valid when the position is reached at another ply.
+#[inline(always)]
+pub fn score_to_tt(score: i32, ply: usize) -> i32 {
+    if score > MATE_BOUND {
+        score + ply as i32
+    } else if score < -MATE_BOUND {
+        score - ply as i32
+    } else {
+        score
+    }
+} //
+
+#[inline(always)]
+pub fn score_from_tt(score: i32, ply: usize) -> i32 {
+    if score > MATE_BOUND {
+        score - ply as i32
+    } else if score < -MATE_BOUND {
+        score + ply as i32
+    } else {
+        score
+    }
+} //
+
+const BUCKET_SIZE: usize = 8;
+
+/// One cache line of entries. Every entry is a single atomic word, so threads
+/// sharing the table never see a half written entry.
+#[repr(C, align(64))]
+#[derive(Default)]
+struct Bucket {
+    entries: [AtomicU64; BUCKET_SIZE],
 }
 
-#[derive(Clone)]
+/// Lock-free table, shared between the UCI thread and the searching threads.
 pub struct TranspositionTable {
-    table: Vec<Option<TTEntry>>,
-    mask: usize,
+    buckets: Box<[Bucket]>,
+    generation: AtomicU8,
 }
 
 impl TranspositionTable {
-    pub fn new(size_pow2: usize) -> Self {
-        let size = 1usize << size_pow2;
+    /// Table using at most `megabytes` of memory, and at least one bucket.
+    pub fn new(megabytes: usize) -> Self {
+        let size = (megabytes * 1024 * 1024 / size_of::<Bucket>()).max(1);
         Self {
-            table: vec![None; size],
-            mask: size - 1,
+            buckets: (0..size).map(|_| Bucket::default()).collect(),
+            generation: AtomicU8::new(0),
         }
     } //
 
+    /// Maps the key onto the table without requiring a power of two size.
     #[inline(always)]
-    fn index(&self, key: u64) -> usize {
-        (key as usize) & self.mask
+    fn bucket(&self, key: u64) -> &Bucket {
+        &self.buckets[((key as u128 * self.buckets.len() as u128) >> 64) as usize]
     } //
 
     #[inline(always)]
-    pub fn probe(&self, key: u64) -> Option<TTEntry> {
-        let entry = self.table[self.index(key)]?;
+    fn generation(&self) -> u8 {
+        self.generation.load(Ordering::Relaxed)
+    } //
 
-        if entry.key != key {
-            return None;
-        }
+    /// Ages every entry by one search.
+    pub fn new_search(&self) {
+        let generation = self.generation().wrapping_add(1) & GENERATION_MASK;
+        self.generation.store(generation, Ordering::Relaxed);
+    } //
 
-        return Some(entry);
+    #[inline(always)]
+    pub fn probe(&self, key: u64) -> Option<TTEntry> {
+        self.bucket(key)
+            .entries
+            .iter()
+            .map(|entry| entry.load(Ordering::Relaxed))
+            .find(|&data| data != 0 && data as u16 == key as u16)
+            .map(TTEntry::unpack)
     } //
 
+    /// `score` has to be normalized with `score_to_tt` already, `bound` comes from the
+    /// score before that, see `Bound::new`.
     #[inline(always)]
-    pub fn store(
-        &mut self,
-        key: u64,
-        depth: i8,
-        score: i32,
-        alpha: i32,
-        beta: i32,
-        best_move: Move,
-        all_searched: bool,
-    ) {
-        let bound = if !all_searched {
-            if score <= alpha {
-                Bound::Upper
-            } else {
-                Bound::Lower
-            }
-        } else {
-            if score <= alpha {
-                Bound::Upper
-            } else if score >= beta {
-                Bound::Lower
-            } else {
-                Bound::Exact
+    pub fn store(&self, key: u64, depth: i8, score: i32, bound: Bound, best_move: Option<Move>) {
+        let generation = self.generation();
+        let entries = &self.bucket(key).entries;
+
+        // Same position first, then an empty entry, then the shallowest and oldest
+        let mut victim = &entries[0];
+        let mut victim_value = i32::MAX;
+
+        for entry in entries {
+            let data = entry.load(Ordering::Relaxed);
+            let old = TTEntry::unpack(data);
+
+            if data != 0 && old.key == key as u16 {
+                // A shallower result of the current search only replaces exact scores
+                if depth < old.depth && old.generation == generation && bound != Bound::Exact {
+                    return;
+                }
+                victim = entry;
+                break;
             }
-        };
-
-        let idx = self.index(key);
-
-        match self.table[idx] {
-            None => {
-                self.table[idx] = Some(TTEntry {
-                    key,
-                    depth,
-                    score,
-                    bound,
-                    best_move,
-                });
+
+            let value = match data {
+                0 => i32::MIN,
+                _ => old.depth as i32 - 8 * old.age(generation),
+            };
+            if value < victim_value {
+                victim = entry;
+                victim_value = value;
             }
-            Some(old) if depth >= old.depth => {
-                self.table[idx] = Some(TTEntry {
-                    key,
-                    depth,
-                    score,
-                    bound,
-                    best_move,
-                });
+        }
+
+        let data = TTEntry {
+            key: key as u16,
+            depth,
+            score,
+            bound,
+            best_move: best_move.map_or(0, Move::compact),
+            generation,
+        }
+        .pack();
+
+        victim.store(data, Ordering::Relaxed);
+    } //
+
+    /// Permille of entries written during the current search, estimated from
+    /// the first thousand.
+    pub fn hashfull(&self) -> usize {
+        let generation = self.generation();
+        let sample = &self.buckets[..self.buckets.len().min(1000 / BUCKET_SIZE)];
+        let used = sample
+            .iter()
+            .flat_map(|bucket| bucket.entries.iter())
+            .map(|entry| entry.load(Ordering::Relaxed))
+            .filter(|&data| data != 0 && TTEntry::unpack(data).generation == generation)
+            .count();
+
+        used * 1000 / (sample.len() * BUCKET_SIZE)
+    } //
+
+    /// Empties the table, safe to call while a search is using it.
+    pub fn clear(&self) {
+        for bucket in self.buckets.iter() {
+            for entry in &bucket.entries {
+                entry.store(0, Ordering::Relaxed);
             }
-            _ => {}
         }
+        self.generation.store(0, Ordering::Relaxed);
     } //
 } //
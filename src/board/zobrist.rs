@@ -28,4 +28,15 @@ pub static Z_CASTLING: Lazy<[u64; 4]> = Lazy::new(|| {
         rng.random::<u64>(), // Black King-side
         rng.random::<u64>(), // Black Queen-side
     ]
-});
\ No newline at end of file
+});
+
+pub static Z_EN_PASSANT: Lazy<[u64; 8]> = Lazy::new(|| {
+    let mut rng = StdRng::seed_from_u64(0xDEADBEEF ^ 0x789ABC);
+    let mut table = [0u64; 8];
+
+    for key in table.iter_mut() {
+        *key = rng.random::<u64>();
+    }
+
+    table
+});
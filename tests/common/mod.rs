@@ -0,0 +1,85 @@
+//! Scaffolding shared by the integration tests.
+#![allow(dead_code)]
+
+use queenfish::board::bishop_magic::init_bishop_magics;
+use queenfish::board::rook_magic::init_rook_magics;
+use queenfish::board::{Board, Engine, Move, SearchConfig};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::Once;
+
+pub const PLAYOUTS_PER_FEN: usize = 20;
+pub const MAX_PLIES: usize = 80;
+
+pub const FENS: [&str; 14] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+    "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1",
+    "r3k2r/8/3Q4/8/8/5q2/8/R3K2R b KQkq - 0 1",
+    "8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1",
+    "3k4/3p4/8/K1P4r/8/8/8/8 b - - 0 1",
+    "4k3/8/8/8/8/8/8/4K2R w K - 0 1",
+];
+
+/// Fills the magic tables once per test binary, tests run on parallel threads.
+pub fn init() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        init_rook_magics();
+        init_bishop_magics();
+    });
+}
+
+/// An engine playing from its own search only, `config` otherwise.
+pub fn engine(config: SearchConfig) -> Engine {
+    init();
+
+    Engine::new(SearchConfig {
+        own_book: false,
+        ..config
+    })
+}
+
+pub fn sorted(moves: &[Move]) -> Vec<Move> {
+    let mut moves = moves.to_vec();
+    moves.sort_by_key(|mv| mv.compact());
+    moves
+}
+
+/// Plays random legal moves from `fen`, calling `visit` on the position before each move
+/// with the moves leading to it, then unwinds every game back to `fen`.
+pub fn random_playouts(fen: &str, seed: u64, mut visit: impl FnMut(&mut Board, &[String])) {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for _ in 0..PLAYOUTS_PER_FEN {
+        let mut board = Board::from_fen(fen).unwrap();
+        let start = board.clone();
+        let mut undo_stack = Vec::new();
+        let mut line = Vec::new();
+
+        for _ in 0..MAX_PLIES {
+            visit(&mut board, &line);
+
+            let moves = board.generate_moves();
+            if moves.is_empty() {
+                break;
+            }
+
+            let mv = moves[rng.random_range(0..moves.len())];
+            line.push(mv.to_uci());
+            undo_stack.push(board.make_move(mv));
+        }
+
+        while let Some(undo) = undo_stack.pop() {
+            board.unmake_move(undo);
+        }
+        assert_eq!(board, start, "unwinding {fen} {}", line.join(" "));
+    }
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use common::init;
+use queenfish::board::fen::{FenErrorKind, FenField};
+use queenfish::board::{Board, FenError};
+
+const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+const KINGS: &str = "4k3/8/8/8/8/8/8/4K3";
+const CORNERS: &str = "r3k2r/8/8/8/8/8/8/R3K2R";
+
+fn error(fen: &str) -> (FenField, FenErrorKind) {
+    let FenError { field, kind } = Board::from_fen(fen).unwrap_err();
+    (field, kind)
+}
+
+fn invalid(value: &str) -> FenErrorKind {
+    FenErrorKind::InvalidValue(value.to_string())
+}
+
+#[test]
+fn placement_errors() {
+    init();
+
+    let cases = [
+        ("", FenErrorKind::Missing),
+        ("8/8/8 w - - 0 1", FenErrorKind::WrongRankCount(3)),
+        (
+            "4k3/8/8/8/8/8/8/4K2 w - - 0 1",
+            FenErrorKind::WrongFileCount { rank: 0, files: 7 },
+        ),
+        (
+            "4k4/8/8/8/8/8/8/4K3 w - - 0 1",
+            FenErrorKind::WrongFileCount { rank: 7, files: 9 },
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4X3 w - - 0 1",
+            FenErrorKind::InvalidPiece('X'),
+        ),
+        (
+            "8/8/8/8/8/8/8/4K3 w - - 0 1",
+            FenErrorKind::KingCount { white: 1, black: 0 },
+        ),
+        (
+            "4k3/8/8/8/8/8/8/3KK3 w - - 0 1",
+            FenErrorKind::KingCount { white: 2, black: 1 },
+        ),
+        (
+            "4k3/8/8/8/8/8/8/P3K3 w - - 0 1",
+            FenErrorKind::PawnOnBackRank(0),
+        ),
+        (
+            "4k2p/8/8/8/8/8/8/4K3 w - - 0 1",
+            FenErrorKind::PawnOnBackRank(63),
+        ),
+    ];
+
+    for (fen, kind) in cases {
+        assert_eq!(error(fen), (FenField::Placement, kind), "{fen}");
+    }
+} //
+
+#[test]
+fn field_errors() {
+    init();
+
+    let cases = [
+        (
+            KINGS.to_string(),
+            FenField::SideToMove,
+            FenErrorKind::Missing,
+        ),
+        (format!("{KINGS} x"), FenField::SideToMove, invalid("x")),
+        (
+            "4k3/8/8/8/8/8/8/4K2r b - - 0 1".to_string(),
+            FenField::SideToMove,
+            FenErrorKind::OpponentInCheck,
+        ),
+        (format!("{CORNERS} w KX"), FenField::Castling, invalid("KX")),
+        (format!("{CORNERS} w KK"), FenField::Castling, invalid("KK")),
+        (
+            format!("{KINGS} w K"),
+            FenField::Castling,
+            FenErrorKind::MissingCastlingPieces('K'),
+        ),
+        (
+            format!("{KINGS} w - e5"),
+            FenField::EnPassant,
+            invalid("e5"),
+        ),
+        (
+            format!("{KINGS} w - i6"),
+            FenField::EnPassant,
+            invalid("i6"),
+        ),
+        (
+            format!("{KINGS} w - e6"),
+            FenField::EnPassant,
+            FenErrorKind::EnPassantWithoutPawn(44),
+        ),
+        (
+            format!("{KINGS} w - - x"),
+            FenField::HalfmoveClock,
+            invalid("x"),
+        ),
+        (
+            format!("{KINGS} w - - 0 0"),
+            FenField::FullmoveNumber,
+            invalid("0"),
+        ),
+        (
+            format!("{START} extra"),
+            FenField::Trailing,
+            FenErrorKind::TooManyFields(7),
+        ),
+    ];
+
+    for (fen, field, kind) in cases {
+        assert_eq!(error(&fen), (field, kind), "{fen}");
+    }
+
+    let err = Board::from_fen(&format!("{START} extra")).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid FEN trailing fields: expected at most 6 fields, found 7"
+    );
+} //
+
+#[test]
+fn optional_fields_default_and_round_trip() {
+    init();
+
+    let board = Board::from_fen(&format!("{KINGS} b")).unwrap();
+    assert_eq!(board.to_fen(), format!("{KINGS} b - - 0 1"));
+
+    for fen in [
+        START,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1",
+        "4k3/8/8/8/8/8/8/4K2R w K - 37 80",
+    ] {
+        assert_eq!(Board::from_fen(fen).unwrap().to_fen(), fen);
+    }
+} //
@@ -0,0 +1,160 @@
+mod common;
+
+use common::init;
+use queenfish::board::{Board, GameStatus, Move};
+
+fn status(fen: &str) -> GameStatus {
+    Board::from_fen(fen).unwrap().game_status()
+}
+
+fn play(board: &mut Board, moves: &[&str]) {
+    for uci in moves {
+        let mv = Move::parse_uci(uci, board).unwrap();
+        board.make_move(mv);
+    }
+}
+
+#[test]
+fn mate_and_stalemate() {
+    init();
+
+    assert_eq!(
+        status("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1"),
+        GameStatus::Checkmate
+    );
+    assert_eq!(
+        status("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1"),
+        GameStatus::Stalemate
+    );
+    assert_eq!(Board::new().game_status(), GameStatus::Ongoing);
+
+    // Mate on the hundredth halfmove is still mate
+    assert_eq!(
+        status("7k/6Q1/6K1/8/8/8/8/8 b - - 100 80"),
+        GameStatus::Checkmate
+    );
+    assert_eq!(
+        status("7k/6Q1/6K1/8/8/8/8/8 b - - 150 80"),
+        GameStatus::Checkmate
+    );
+} //
+
+#[test]
+fn insufficient_material() {
+    init();
+
+    for fen in [
+        "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/2B1K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/1N2K3 b - - 0 1",
+        "2b1k3/8/8/8/8/8/8/4KB2 w - - 0 1",
+        "4k3/8/8/8/8/8/8/B1B1K3 w - - 0 1",
+    ] {
+        let mut board = Board::from_fen(fen).unwrap();
+        assert_eq!(
+            board.game_status(),
+            GameStatus::InsufficientMaterial,
+            "{fen}"
+        );
+        assert!(board.is_draw(), "{fen}");
+    }
+
+    // Mate remains possible
+    for fen in [
+        "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/R3K3 w - - 0 1",
+        "1b2k3/8/8/8/8/8/8/4KB2 w - - 0 1",
+        "4k3/8/8/8/8/8/8/1N2KB2 w - - 0 1",
+        "4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1",
+        "1n2k3/8/8/8/8/8/8/4KB2 w - - 0 1",
+    ] {
+        let mut board = Board::from_fen(fen).unwrap();
+        assert_eq!(board.game_status(), GameStatus::Ongoing, "{fen}");
+        assert!(!board.is_draw(), "{fen}");
+    }
+} //
+
+#[test]
+fn threefold_and_fivefold_repetition() {
+    init();
+
+    let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+    let mut board = Board::new();
+
+    play(&mut board, &shuffle);
+    assert_eq!(board.repetition_count(), 2);
+    assert_eq!(board.game_status(), GameStatus::Ongoing);
+    assert!(!board.is_draw());
+
+    play(&mut board, &shuffle);
+    assert_eq!(board.repetition_count(), 3);
+    assert_eq!(board.game_status(), GameStatus::ThreefoldRepetition);
+    assert!(board.is_draw());
+    assert!(!board.game_status().is_game_over());
+
+    play(&mut board, &shuffle);
+    play(&mut board, &shuffle);
+    assert_eq!(board.game_status(), GameStatus::FivefoldRepetition);
+    assert!(board.game_status().is_game_over());
+
+    // A pawn move makes the earlier positions unreachable
+    play(&mut board, &["e2e4", "e7e5"]);
+    assert_eq!(board.repetition_count(), 1);
+    play(&mut board, &shuffle);
+    play(&mut board, &shuffle);
+    assert_eq!(board.game_status(), GameStatus::ThreefoldRepetition);
+    play(&mut board, &["d2d4"]);
+    assert_eq!(board.repetition_count(), 1);
+    assert_eq!(board.game_status(), GameStatus::Ongoing);
+} //
+
+#[test]
+fn fifty_and_seventy_five_move_rules() {
+    init();
+
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 80").unwrap();
+    assert_eq!(board.game_status(), GameStatus::Ongoing);
+    assert!(!board.is_draw());
+
+    play(&mut board, &["a1a2"]);
+    assert_eq!(board.halfmove_clock, 100);
+    assert_eq!(board.game_status(), GameStatus::FiftyMoveRule);
+    assert!(board.is_draw());
+    assert!(!board.game_status().is_game_over());
+
+    assert_eq!(
+        status("4k3/8/8/8/8/8/8/R3K3 w - - 150 100"),
+        GameStatus::SeventyFiveMoveRule
+    );
+    assert_eq!(
+        status("4k3/8/8/8/8/8/4P3/4K3 w - - 149 100"),
+        GameStatus::FiftyMoveRule
+    );
+
+    // Mate on the last move beats the rule
+    let mut board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 99 80").unwrap();
+    play(&mut board, &["a1a8"]);
+    assert_eq!(board.game_status(), GameStatus::Checkmate);
+    assert!(!board.is_draw());
+} //
+
+#[test]
+fn status_flags() {
+    let draws = [
+        GameStatus::Stalemate,
+        GameStatus::ThreefoldRepetition,
+        GameStatus::FivefoldRepetition,
+        GameStatus::FiftyMoveRule,
+        GameStatus::SeventyFiveMoveRule,
+        GameStatus::InsufficientMaterial,
+    ];
+    for status in draws {
+        assert!(status.is_draw(), "{status}");
+    }
+    assert!(!GameStatus::Checkmate.is_draw());
+    assert!(!GameStatus::Ongoing.is_draw());
+
+    assert!(!GameStatus::Checkmate.has_legal_moves());
+    assert!(!GameStatus::Stalemate.has_legal_moves());
+    assert!(GameStatus::ThreefoldRepetition.has_legal_moves());
+} //
@@ -0,0 +1,56 @@
+mod common;
+
+use common::engine;
+use queenfish::board::search::MATE_SCORE;
+use queenfish::board::{Board, SearchConfig, SearchLimits};
+
+#[test]
+fn helpers_agree_on_a_forced_mate() {
+    let mut engine = engine(SearchConfig::default());
+    engine.set_threads(4).unwrap();
+
+    let mut board = Board::from_fen("7k/8/8/8/8/8/1R6/R5K1 w - - 0 1").unwrap();
+    let result = engine.search(&board, &SearchLimits::depth(6));
+
+    assert_eq!(result.score, MATE_SCORE - 3);
+    assert_eq!(result.nodes, engine.nodes());
+
+    // Every helper searched, not only the main thread
+    let thread_nodes = engine.thread_nodes();
+    assert_eq!(thread_nodes.len(), 4);
+    assert!(
+        thread_nodes.iter().all(|&nodes| nodes > 0),
+        "{thread_nodes:?}"
+    );
+    assert_eq!(thread_nodes.iter().sum::<u64>(), result.nodes);
+    for mv in &result.pv {
+        assert!(board.generate_moves().contains(mv), "{}", mv.to_uci());
+        board.make_move(*mv);
+    }
+} //
+
+/// `go nodes` bounds the nodes of all threads together, not of each one.
+#[test]
+fn node_limit_is_shared_between_threads() {
+    const LIMIT: u64 = 200_000;
+    let board = Board::new();
+
+    for threads in [1, 4] {
+        let mut engine = engine(SearchConfig::default());
+        engine.set_threads(threads).unwrap();
+        let result = engine.search(&board, &SearchLimits::nodes(LIMIT));
+
+        // Threads only see each other's nodes every few thousand nodes
+        let slack = threads as u64 * 4096;
+        assert!(
+            result.nodes <= LIMIT + slack,
+            "{threads} threads: {}",
+            result.nodes
+        );
+        assert!(
+            result.nodes >= LIMIT / 2,
+            "{threads} threads: {}",
+            result.nodes
+        );
+    }
+} //
@@ -0,0 +1,123 @@
+mod common;
+
+use common::{FENS, engine, init, random_playouts, sorted};
+use queenfish::board::history::History;
+use queenfish::board::move_picker::MovePicker;
+use queenfish::board::search::MATE_BOUND;
+use queenfish::board::{Board, Move, SearchConfig, SearchLimits};
+use smallvec::SmallVec;
+
+// (fen, depth, expected nodes)
+const PERFT: [(&str, i32, i64); 8] = [
+    (
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        3,
+        97_862,
+    ),
+    ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 5, 674_624),
+    (
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        3,
+        9_467,
+    ),
+    ("3k4/3p4/8/K1P4r/8/8/8/8 b - - 0 1", 6, 1_134_888),
+    ("8/8/4k3/8/2p5/8/B2P2K1/8 w - - 0 1", 6, 1_015_133),
+    ("8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1", 6, 1_440_467),
+    ("r3k2r/8/3Q4/8/8/5q2/8/R3K2R b KQkq - 0 1", 4, 1_720_476),
+    ("8/8/1P2K3/8/2n5/1q6/8/5k2 b - - 0 1", 5, 1_004_658),
+];
+
+/// Pseudo-legal moves that don't castle through check nor leave the king in check.
+fn legal_by_make_unmake(board: &mut Board) -> Vec<Move> {
+    let mut pseudo = SmallVec::new();
+    board.generate_pesudo_moves(&mut pseudo);
+
+    pseudo
+        .into_iter()
+        .filter(|mv| {
+            if mv.is_castling() && board.is_castling_through_check(*mv) {
+                return false;
+            }
+            let undo = board.make_move(*mv);
+            let legal = !board.is_king_in_check(board.opposite_turn());
+            board.unmake_move(undo);
+            legal
+        })
+        .collect()
+}
+
+#[test]
+fn legal_perft_matches_known_counts() {
+    init();
+
+    for (fen, depth, expected) in PERFT {
+        let mut board = Board::from_fen(fen).unwrap();
+        assert_eq!(board.perft_legal(depth), expected, "{fen}");
+        assert_eq!(
+            board.perft(0, depth.min(4)),
+            board.perft_legal(depth.min(4)),
+            "{fen}"
+        );
+
+        for legal_move_gen in [true, false] {
+            let divided = board.perft_divide(depth.min(3), legal_move_gen);
+            let nodes: i64 = divided.iter().map(|(_, nodes)| nodes).sum();
+            assert_eq!(nodes, board.perft_legal(depth.min(3)), "{fen}");
+        }
+    }
+} //
+
+#[test]
+fn perft_below_one_ply_does_not_recurse() {
+    init();
+
+    let mut board = Board::new();
+    for depth in [0, -1] {
+        assert_eq!(board.perft_legal(depth), 1);
+        assert!(board.perft_divide(depth, true).is_empty());
+        assert!(board.perft_divide(depth, false).is_empty());
+    }
+} //
+
+/// Along random games the legal generator and the picker with a `Legality` agree
+/// with making every pseudo-legal move.
+#[test]
+fn legal_moves_match_make_unmake() {
+    init();
+    let history = History::default();
+
+    for (seed, fen) in FENS.iter().enumerate() {
+        random_playouts(fen, seed as u64, |board, _| {
+            let expected = sorted(&legal_by_make_unmake(board));
+
+            let mut legal = SmallVec::new();
+            board.generate_legal_moves(&mut legal);
+            assert_eq!(sorted(&legal), expected, "{}", board.to_fen());
+
+            let legality = Some(board.legality());
+            let mut picker = MovePicker::new(None, [None; 2], None, legality);
+            let mut picked = Vec::new();
+            while let Some(mv) = picker.next(board, &history, &[None; 2]) {
+                picked.push(mv);
+            }
+            assert_eq!(sorted(&picked), expected, "{}", board.to_fen());
+        });
+    }
+} //
+
+#[test]
+fn both_generators_find_the_mate() {
+    init();
+
+    for legal_move_gen in [true, false] {
+        let mut engine = engine(SearchConfig {
+            legal_move_gen,
+            ..Default::default()
+        });
+        let mut board = Board::from_fen("7k/8/8/8/8/8/1R6/R5K1 w - - 0 1").unwrap();
+        let result = engine.search(&board, &SearchLimits::depth(5));
+
+        assert!(board.generate_moves().contains(&result.best_move.unwrap()));
+        assert!(result.score > MATE_BOUND, "legal_move_gen {legal_move_gen}");
+    }
+} //
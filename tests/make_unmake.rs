@@ -0,0 +1,108 @@
+mod common;
+
+use common::{FENS, init, random_playouts};
+use queenfish::board::Board;
+
+/// Checks every incrementally maintained field against a from-scratch recomputation.
+fn assert_consistent(board: &Board, context: &str) {
+    assert_eq!(board.hash, board.compute_hash(), "hash after {context}");
+    assert_eq!(board.mat_eval, board.pieces_score(), "mat_eval after {context}");
+    assert_eq!(
+        (board.mg_pst_eval, board.eg_pst_eval),
+        board.generate_pst_score(),
+        "pst eval after {context}"
+    );
+    assert_eq!(
+        board.mobility_eval,
+        board.generate_mobility_eval(),
+        "mobility_eval after {context}"
+    );
+    assert_eq!(
+        board.piece_at,
+        board.generate_piece_at(),
+        "piece_at after {context}"
+    );
+    assert_eq!(board.occupied, board.get_all_bits(), "occupied after {context}");
+    assert_eq!(
+        board.number_of_pieces,
+        board.generate_pieces_count() as usize,
+        "number_of_pieces after {context}"
+    );
+    assert_eq!(
+        board.number_of_pawns,
+        board.generate_pawns_count() as usize,
+        "number_of_pawns after {context}"
+    );
+    assert_eq!(
+        board.history.last(),
+        Some(&board.hash),
+        "history after {context}"
+    );
+}
+
+#[test]
+fn make_unmake_restores_every_field() {
+    init();
+
+    for (seed, fen) in FENS.iter().enumerate() {
+        random_playouts(fen, seed as u64, |board, line| {
+            let before = board.clone();
+
+            for mv in board.generate_moves() {
+                let undo = board.make_move(mv);
+                board.unmake_move(undo);
+                assert_eq!(
+                    *board,
+                    before,
+                    "{fen} {} {}",
+                    line.join(" "),
+                    mv.to_uci()
+                );
+            }
+        });
+    }
+}
+
+#[test]
+fn incremental_state_matches_recomputation() {
+    init();
+
+    for (seed, fen) in FENS.iter().enumerate() {
+        random_playouts(fen, seed as u64 + 1000, |board, line| {
+            assert_consistent(board, &format!("{fen} {}", line.join(" ")));
+
+            for mv in board.generate_moves() {
+                let undo = board.make_move(mv);
+                assert_consistent(
+                    board,
+                    &format!("{fen} {} {}", line.join(" "), mv.to_uci()),
+                );
+                board.unmake_move(undo);
+            }
+        });
+    }
+}
+
+#[test]
+fn null_move_restores_every_field() {
+    init();
+
+    for (seed, fen) in FENS.iter().enumerate() {
+        random_playouts(fen, seed as u64 + 2000, |board, line| {
+            if board.is_king_in_check(board.turn) {
+                return;
+            }
+
+            let before = board.clone();
+            let en_passant = board.make_null_move();
+            assert_eq!(
+                board.hash,
+                board.compute_hash(),
+                "null move hash {fen} {}",
+                line.join(" ")
+            );
+            board.unmake_null_move(en_passant);
+            assert_eq!(*board, before, "null move {fen} {}", line.join(" "));
+        });
+    }
+}
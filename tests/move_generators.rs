@@ -0,0 +1,62 @@
+mod common;
+
+use common::{FENS, init, random_playouts, sorted};
+use queenfish::board::{Board, Move, Turn};
+use smallvec::SmallVec;
+
+/// The moved piece attacks the enemy king from its destination.
+fn gives_direct_check(board: &mut Board, mv: Move) -> bool {
+    let undo = board.make_move(mv);
+    let king_index = match board.turn {
+        Turn::WHITE => 5,
+        Turn::BLACK => 11,
+    };
+    let king = (0..64)
+        .find(|&square| board.piece_at[square].is_some_and(|p| p.piece_index() == king_index))
+        .unwrap();
+    let occupied = (0..64)
+        .filter(|&square| board.piece_at[square].is_some())
+        .fold(0, |occupied, square| occupied | 1u64 << square);
+    let attackers = board.attackers_to(king, occupied);
+    let check = attackers & (1u64 << mv.to()) != 0;
+    board.unmake_move(undo);
+    check
+}
+
+/// Captures and quiets split the pseudo-legal moves, and the quiet checks are
+/// exactly the quiet moves checking with the moved piece, kings aside.
+#[test]
+fn split_generators_cover_the_pseudo_legal_moves() {
+    init();
+
+    for (seed, fen) in FENS.iter().enumerate() {
+        random_playouts(fen, seed as u64, |board, _| {
+            let (mut pseudo, mut captures, mut quiets, mut checks) = (
+                SmallVec::new(),
+                SmallVec::new(),
+                SmallVec::new(),
+                SmallVec::new(),
+            );
+            board.generate_pesudo_moves(&mut pseudo);
+            board.generate_captures(&mut captures);
+            board.generate_quiets(&mut quiets);
+            board.generate_quiet_checks(&mut checks);
+            let fen = board.to_fen();
+
+            for mv in &captures {
+                let queen_promotion = mv.to_uci().ends_with('q');
+                assert!(mv.is_capture() || queen_promotion, "{fen} {}", mv.to_uci());
+            }
+            let union = [captures.as_slice(), quiets.as_slice()].concat();
+            assert_eq!(sorted(&union), sorted(&pseudo), "{fen}");
+
+            let expected: Vec<Move> = quiets
+                .iter()
+                .copied()
+                .filter(|mv| mv.piece().piece_index() % 6 != 5 && !mv.is_promotion())
+                .filter(|mv| gives_direct_check(board, *mv))
+                .collect();
+            assert_eq!(sorted(&checks), sorted(&expected), "{fen}");
+        });
+    }
+} //
@@ -0,0 +1,105 @@
+mod common;
+
+use common::{FENS, init, random_playouts, sorted};
+use queenfish::board::Move;
+use queenfish::board::history::History;
+use queenfish::board::move_picker::MovePicker;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use smallvec::SmallVec;
+use std::collections::HashSet;
+
+/// Moves from earlier positions of a game and random compact moves are checked
+/// against the generator, the way the search checks TT moves and killers.
+#[test]
+fn pseudo_legality_matches_the_generator() {
+    init();
+    let mut rng = StdRng::seed_from_u64(0x5EED);
+    let mut seen: HashSet<Move> = HashSet::new();
+
+    for (seed, fen) in FENS.iter().enumerate() {
+        random_playouts(fen, seed as u64, |board, line| {
+            if line.is_empty() {
+                seen.clear();
+            }
+            let mut pseudo = SmallVec::new();
+            board.generate_pesudo_moves(&mut pseudo);
+            seen.extend(pseudo.iter().copied());
+
+            for mv in &seen {
+                assert_eq!(
+                    board.is_pseudo_legal(*mv),
+                    pseudo.contains(mv),
+                    "{} {}",
+                    board.to_fen(),
+                    mv.to_uci()
+                );
+            }
+            for mv in &pseudo {
+                assert_eq!(
+                    board.move_from_compact(mv.compact()),
+                    Some(*mv),
+                    "{} {}",
+                    board.to_fen(),
+                    mv.to_uci()
+                );
+            }
+            // Index collisions in the TT hand out arbitrary compact moves
+            for _ in 0..200 {
+                let compact: u16 = rng.random_range(0..5 << 12);
+                let expected = pseudo.iter().find(|mv| mv.compact() == compact).copied();
+                assert_eq!(
+                    board.move_from_compact(compact),
+                    expected,
+                    "{} {compact}",
+                    board.to_fen()
+                );
+            }
+        });
+    }
+} //
+
+/// The picker hands out every pseudo-legal move exactly once, whatever the TT move,
+/// killers and countermove are, and the TT move before anything else.
+#[test]
+fn move_picker_hands_out_every_move_once() {
+    init();
+    let mut rng = StdRng::seed_from_u64(0x91C4);
+    let history = History::default();
+    let mut seen: Vec<Move> = Vec::new();
+
+    for (seed, fen) in FENS.iter().enumerate() {
+        random_playouts(fen, seed as u64 + 1000, |board, line| {
+            if line.is_empty() {
+                seen.clear();
+            }
+            let mut pseudo = SmallVec::new();
+            board.generate_pesudo_moves(&mut pseudo);
+            seen.extend(pseudo.iter().copied());
+
+            // Killers and countermoves come from other positions
+            let mut random_move = || Some(seen[rng.random_range(0..seen.len())]);
+            let killers = [random_move(), random_move()];
+            let countermove = random_move();
+            let tt_move = Some(pseudo[rng.random_range(0..pseudo.len())]);
+
+            let mut picker = MovePicker::new(tt_move, killers, countermove, None);
+            let mut picked = Vec::new();
+            while let Some(mv) = picker.next(board, &history, &[None; 2]) {
+                picked.push(mv);
+            }
+            assert_eq!(picked.first().copied(), tt_move, "{}", board.to_fen());
+
+            let mut expected = sorted(&pseudo);
+            assert_eq!(sorted(&picked), expected, "{}", board.to_fen());
+
+            let mut picker = MovePicker::quiescence(false, None);
+            let mut noisy = Vec::new();
+            while let Some(mv) = picker.next(board, &history, &[None; 2]) {
+                noisy.push(mv);
+            }
+            expected.retain(|mv| mv.is_capture() || mv.to_uci().ends_with('q'));
+            assert_eq!(sorted(&noisy), expected, "{}", board.to_fen());
+        });
+    }
+} //
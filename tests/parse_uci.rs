@@ -0,0 +1,63 @@
+mod common;
+
+use common::init;
+use queenfish::board::{Board, Move, MoveParseError};
+
+const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[test]
+fn parses_legal_moves_and_promotions() {
+    init();
+
+    let board = Board::from_fen(START).unwrap();
+    assert_eq!(Move::parse_uci("e2e4", &board).unwrap().to_uci(), "e2e4");
+    assert_eq!(Move::parse_uci("g1f3", &board).unwrap().to_uci(), "g1f3");
+
+    let board = Board::from_fen("8/4P1k1/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(Move::parse_uci("e7e8n", &board).unwrap().to_uci(), "e7e8n");
+    assert_eq!(Move::parse_uci("e7e8Q", &board).unwrap().to_uci(), "e7e8q");
+} //
+
+#[test]
+fn rejects_malformed_and_illegal_moves() {
+    init();
+    let board = Board::from_fen(START).unwrap();
+
+    let cases = [
+        ("e2", MoveParseError::InvalidLength(2)),
+        ("e2e4q1", MoveParseError::InvalidLength(6)),
+        ("i2e4", MoveParseError::InvalidSquare("i2".to_string())),
+        ("e2e9", MoveParseError::InvalidSquare("e9".to_string())),
+        ("e2e4k", MoveParseError::InvalidPromotion('k')),
+        ("e2e5", MoveParseError::Illegal("e2e5".to_string())),
+        ("e7e8q", MoveParseError::Illegal("e7e8q".to_string())),
+    ];
+    for (uci, error) in cases {
+        assert_eq!(Move::parse_uci(uci, &board), Err(error), "{uci}");
+    }
+} //
+
+/// Multi-byte characters can't be sliced like ASCII squares, they are reported
+/// as such rather than as a square or length error.
+#[test]
+fn rejects_non_ascii_input() {
+    init();
+    let board = Board::from_fen(START).unwrap();
+
+    let cases = [
+        ("e2€", '€'),
+        ("€e4", '€'),
+        ("e2e€", '€'),
+        ("é2e4", 'é'),
+        ("e2é4", 'é'),
+        ("e2e4€", '€'),
+        ("€€", '€'),
+    ];
+    for (uci, c) in cases {
+        let error = MoveParseError::InvalidCharacter(c);
+        assert_eq!(Move::parse_uci(uci, &board), Err(error), "{uci}");
+    }
+
+    let error = Move::parse_uci("e2e4€", &board).unwrap_err();
+    assert_eq!(error.to_string(), "invalid character '€'");
+} //
@@ -0,0 +1,178 @@
+mod common;
+
+use common::init;
+use queenfish::board::pgn::{PgnErrorKind, PgnGame};
+use queenfish::board::{Board, Move, MoveParseError};
+
+const ANNOTATED: &str = r#"[Event "Casual \"blitz\" game"]
+[Site "Cairo"]
+[Date "2024.01.02"]
+[Round "1"]
+[White "Alice"]
+[Black "Bob"]
+[Result "1-0"]
+[Annotator "Carol"]
+
+{Opening comment} 1. e4 $1 {Best by test} e5 2. Nf3 (2. f4 exf4 (2... d5 $5 {Counter
+gambit} 3. exd5) 3. Nf3) (2. Bc4) 2... Nc6 $2 3. Bb5!? ; rest of line
+a6 (3... Nf6 4. O-O) 4. Ba4 1-0
+"#;
+
+#[test]
+fn round_trips_an_annotated_game() {
+    init();
+
+    let game = PgnGame::parse(ANNOTATED).unwrap();
+    let pgn = game.to_pgn();
+    let reparsed = PgnGame::parse(&pgn).unwrap();
+
+    assert_eq!(reparsed, game, "{pgn}");
+    assert_eq!(reparsed.to_pgn(), pgn);
+
+    assert_eq!(game.tag("Event"), Some("Casual \"blitz\" game"));
+    assert_eq!(game.tag("Annotator"), Some("Carol"));
+    assert_eq!(game.tag("Result"), Some("1-0"));
+    assert_eq!(game.comment.as_deref(), Some("Opening comment"));
+    assert!(pgn.starts_with("[Event \"Casual \\\"blitz\\\" game\"]\n[Site \"Cairo\"]\n"));
+
+    let [e4, e5, nf3, nc6, bb5, a6, ba4] = game.moves.as_slice() else {
+        panic!("mainline of {} moves", game.moves.len());
+    };
+    assert_eq!(e4.nags, [1]);
+    assert_eq!(e4.comments, ["Best by test"]);
+    assert!(e5.nags.is_empty() && e5.variations.is_empty());
+    assert_eq!(nc6.nags, [2]);
+    assert_eq!(bb5.nags, [5]);
+    assert_eq!(bb5.comments, ["rest of line"]);
+    assert_eq!(ba4.mv.to_uci(), "b5a4");
+
+    // (2. f4 exf4 (2... d5 3. exd5) 3. Nf3) and (2. Bc4)
+    assert_eq!(nf3.variations.len(), 2);
+    let king_gambit = &nf3.variations[0];
+    assert_eq!(king_gambit.len(), 3);
+    assert_eq!(king_gambit[1].mv.to_uci(), "e5f4");
+    let counter_gambit = &king_gambit[1].variations[0];
+    assert_eq!(counter_gambit[0].mv.to_uci(), "d7d5");
+    assert_eq!(counter_gambit[0].nags, [5]);
+    assert_eq!(counter_gambit[0].comments, ["Counter gambit"]);
+    assert_eq!(nf3.variations[1][0].mv.to_uci(), "f1c4");
+    assert_eq!(a6.variations[0].len(), 2);
+
+    let movetext = pgn.split("\n\n").nth(1).unwrap().replace('\n', " ");
+    assert_eq!(
+        movetext,
+        "{Opening comment} 1. e4 $1 {Best by test} 1... e5 2. Nf3 (2. f4 exf4 (2... d5 $5 \
+         {Counter gambit} 3. exd5) 3. Nf3) (2. Bc4) 2... Nc6 $2 3. Bb5 $5 {rest of line} \
+         3... a6 (3... Nf6 4. O-O) 4. Ba4 1-0"
+    );
+} //
+
+#[test]
+fn result_tokens_set_the_result_tag() {
+    init();
+
+    for result in ["1-0", "0-1", "1/2-1/2", "*"] {
+        let game = PgnGame::parse(&format!("1. e4 e5 {result}")).unwrap();
+        assert_eq!(game.tag("Result"), Some(result));
+        assert_eq!(game.moves.len(), 2);
+
+        let pgn = game.to_pgn();
+        assert!(pgn.contains(&format!("[Result \"{result}\"]")), "{pgn}");
+        assert!(pgn.ends_with(&format!("1. e4 e5 {result}\n\n")), "{pgn}");
+    }
+
+    // Games of a database are separated by their result
+    let games = PgnGame::parse_all("1. d4 1-0\n\n1. c4 c5 0-1\n\n").unwrap();
+    let results: Vec<_> = games.iter().map(|game| game.tag("Result")).collect();
+    assert_eq!(results, [Some("1-0"), Some("0-1")]);
+} //
+
+/// Movetext lines stay below 80 columns and are filled up before wrapping.
+#[test]
+fn wraps_movetext_at_80_columns() {
+    init();
+
+    let mut board = Board::new();
+    let mut game = PgnGame::new();
+    for _ in 0..30 {
+        for uci in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+            let mv = Move::parse_uci(uci, &board).unwrap();
+            board.make_move(mv);
+            game.push_move(mv);
+        }
+    }
+    let comment = "a comment long enough to be split over two lines of movetext".repeat(2);
+    game.moves[3].comments.push(comment);
+
+    let pgn = game.to_pgn();
+    let movetext: Vec<&str> = pgn.split("\n\n").nth(1).unwrap().lines().collect();
+
+    assert!(movetext.len() > 5, "{pgn}");
+    for (index, line) in movetext.iter().enumerate() {
+        assert!(line.len() < 80, "{line}");
+        assert!(!line.starts_with(' ') && !line.ends_with(' '), "{line}");
+
+        // The first token of the next line would not have fit
+        if let Some(next) = movetext.get(index + 1) {
+            let first = next.split(' ').next().unwrap();
+            assert!(line.len() + 1 + first.len() >= 80, "{line}\n{next}");
+        }
+    }
+    assert!(PgnGame::parse(&pgn).unwrap() == game, "{pgn}");
+} //
+
+#[test]
+fn rejects_malformed_movetext() {
+    init();
+
+    let cases = [
+        ("1. e4 {never closed", 1, PgnErrorKind::UnterminatedComment),
+        ("1. e4 (1. d4 e5", 1, PgnErrorKind::UnbalancedVariation),
+        ("1. e4 e5 ) 2. Nf3", 1, PgnErrorKind::UnbalancedVariation),
+        ("( 1. e4 ) 1. d4", 1, PgnErrorKind::UnbalancedVariation),
+        (
+            "1. e4 e5\n2. Nf3 (2. f4 ))",
+            2,
+            PgnErrorKind::UnbalancedVariation,
+        ),
+        (
+            "$1 1. e4",
+            1,
+            PgnErrorKind::UnexpectedToken("$1".to_string()),
+        ),
+        (
+            "1. e4 $x",
+            1,
+            PgnErrorKind::UnexpectedToken("$".to_string()),
+        ),
+        (
+            "1. e4 !!",
+            1,
+            PgnErrorKind::UnexpectedToken("!!".to_string()),
+        ),
+        (
+            "[Event \"unterminated]\n1. e4",
+            1,
+            PgnErrorKind::MalformedTag,
+        ),
+        ("[Event]\n1. e4", 1, PgnErrorKind::MalformedTag),
+        (
+            "1. e4 e5\n2. Ke3",
+            2,
+            PgnErrorKind::InvalidMove(MoveParseError::Illegal("Ke3".to_string())),
+        ),
+        (
+            "1. e4 e5 2. Zz9",
+            1,
+            PgnErrorKind::InvalidMove(MoveParseError::InvalidSan("Zz9".to_string())),
+        ),
+    ];
+
+    for (pgn, line, kind) in cases {
+        let err = PgnGame::parse(pgn).unwrap_err();
+        assert_eq!((err.line, err.kind), (line, kind), "{pgn}");
+    }
+
+    let err = PgnGame::parse("[FEN \"8/8/8 w - - 0 1\"]\n1. e4").unwrap_err();
+    assert!(matches!(err.kind, PgnErrorKind::InvalidFen(_)), "{err}");
+} //
@@ -0,0 +1,51 @@
+mod common;
+
+use common::engine;
+use queenfish::board::search::MATE_SCORE;
+use queenfish::board::{Board, SearchConfig, SearchLimits};
+
+const FENS: [&str; 3] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+];
+
+#[test]
+fn pv_is_a_legal_line_starting_with_the_best_move() {
+    let mut engine = engine(SearchConfig::default());
+
+    for fen in FENS {
+        let mut board = Board::from_fen(fen).unwrap();
+        let result = engine.search(&board, &SearchLimits::depth(5));
+
+        assert_eq!(result.pv.first().copied(), result.best_move, "{fen}");
+        assert_eq!(result.pv.get(1), result.ponder_move.as_ref(), "{fen}");
+        assert!(result.pv.len() > 1, "{fen}");
+
+        for mv in &result.pv {
+            assert!(board.generate_moves().contains(mv), "{fen}: {}", mv.to_uci());
+            board.make_move(*mv);
+        }
+    }
+} //
+
+/// Checkmated and stalemated sides have no move to play, helpers included.
+#[test]
+fn no_best_move_without_legal_moves() {
+    let mut engine = engine(SearchConfig::default());
+    engine.set_threads(2).unwrap();
+
+    // (fen, score)
+    for (fen, score) in [
+        ("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1", -MATE_SCORE),
+        ("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", 0),
+    ] {
+        let board = Board::from_fen(fen).unwrap();
+        let result = engine.search(&board, &SearchLimits::depth(4));
+
+        assert_eq!(result.best_move, None, "{fen}");
+        assert_eq!(result.ponder_move, None, "{fen}");
+        assert!(result.pv.is_empty(), "{fen}");
+        assert_eq!(result.score, score, "{fen}");
+    }
+} //
@@ -0,0 +1,162 @@
+mod common;
+
+use common::init;
+use queenfish::board::{Board, Move, MoveParseError};
+
+const KNIGHTS: &str = "4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1";
+const ROOKS: &str = "4k3/8/8/R7/8/8/8/R3K3 w - - 0 1";
+const QUEENS: &str = "4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1";
+const CASTLING: &str = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+
+/// `(fen, uci, san)`: the SAN of the move and the move back from it.
+fn assert_san(cases: &[(&str, &str, &str)]) {
+    for (fen, uci, san) in cases {
+        let board = Board::from_fen(fen).unwrap();
+        let mv = Move::parse_uci(uci, &board).unwrap();
+
+        assert_eq!(mv.to_san(&board), *san, "{fen} {uci}");
+        assert_eq!(Move::from_san(san, &board), Ok(mv), "{fen} {san}");
+    }
+}
+
+#[test]
+fn disambiguates_by_file_rank_or_square() {
+    init();
+
+    assert_san(&[
+        (KNIGHTS, "b1d2", "Nbd2"),
+        (KNIGHTS, "f1d2", "Nfd2"),
+        (KNIGHTS, "b1c3", "Nc3"),
+        (ROOKS, "a1a3", "R1a3"),
+        (ROOKS, "a5a3", "R5a3"),
+        (ROOKS, "a5h5", "Rh5"),
+        (QUEENS, "a1b2", "Qa1b2"),
+        (QUEENS, "c1b2", "Qcb2"),
+        (QUEENS, "a3b2", "Q3b2"),
+    ]);
+} //
+
+#[test]
+fn promotions_with_and_without_capture() {
+    init();
+
+    assert_san(&[
+        ("8/4P1k1/8/8/8/8/8/4K3 w - - 0 1", "e7e8q", "e8=Q"),
+        ("8/4P1k1/8/8/8/8/8/4K3 w - - 0 1", "e7e8r", "e8=R"),
+        ("8/4P1k1/8/8/8/8/8/4K3 w - - 0 1", "e7e8n", "e8=N+"),
+        ("3r2k1/4P3/8/8/8/8/8/4K3 w - - 0 1", "e7d8q", "exd8=Q+"),
+        ("3r2k1/4P3/8/8/8/8/8/4K3 w - - 0 1", "e7d8b", "exd8=B"),
+        ("4k3/8/8/8/8/8/1p6/R3K3 b - - 0 1", "b2a1q", "bxa1=Q+"),
+        ("4k3/8/8/8/8/8/1p6/R3K3 b - - 0 1", "b2b1n", "b1=N"),
+    ]);
+
+    // Relaxed forms of the same promotion
+    let board = Board::from_fen("3r2k1/4P3/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let mv = Move::parse_uci("e7d8q", &board).unwrap();
+    for san in ["exd8Q", "ed8=Q", "e7d8q", "exd8=Q+"] {
+        assert_eq!(Move::from_san(san, &board), Ok(mv), "{san}");
+    }
+} //
+
+#[test]
+fn both_castlings_for_both_sides() {
+    init();
+
+    let black = CASTLING.replace(" w ", " b ");
+    assert_san(&[
+        (CASTLING, "e1g1", "O-O"),
+        (CASTLING, "e1c1", "O-O-O"),
+        (&black, "e8g8", "O-O"),
+        (&black, "e8c8", "O-O-O"),
+    ]);
+
+    let board = Board::from_fen(CASTLING).unwrap();
+    let short = Move::parse_uci("e1g1", &board).unwrap();
+    assert_eq!(Move::from_san("0-0", &board), Ok(short));
+} //
+
+#[test]
+fn check_and_mate_suffixes() {
+    init();
+
+    assert_san(&[
+        ("7k/8/8/8/8/8/8/R5K1 w - - 0 1", "a1a8", "Ra8+"),
+        ("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8", "Ra8#"),
+        ("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a7", "Ra7"),
+        ("4k3/8/8/8/8/8/5PPP/6K1 w - - 0 1", "g2g4", "g4"),
+        ("r3k3/8/8/8/8/8/8/3K4 b q - 0 1", "e8c8", "O-O-O+"),
+    ]);
+
+    // Suffixes are optional when parsing, wrong ones are ignored
+    let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    let mate = Move::parse_uci("a1a8", &board).unwrap();
+    for san in ["Ra8", "Ra8+", "Ra8#", "Ra8!?"] {
+        assert_eq!(Move::from_san(san, &board), Ok(mate), "{san}");
+    }
+} //
+
+#[test]
+fn rejects_ambiguous_and_illegal_san() {
+    init();
+
+    const PROMOTION: &str = "8/4P1k1/8/8/8/8/8/4K3 w - - 0 1";
+
+    let ambiguous = [
+        (KNIGHTS, "Nd2"),
+        (ROOKS, "Ra3"),
+        (QUEENS, "Qab2"),
+        (QUEENS, "Q1b2"),
+        (PROMOTION, "e8"),
+    ];
+    for (fen, san) in ambiguous {
+        let board = Board::from_fen(fen).unwrap();
+        let error = MoveParseError::Ambiguous(san.to_string());
+        assert_eq!(Move::from_san(san, &board), Err(error), "{fen} {san}");
+    }
+
+    let illegal = [
+        (KNIGHTS, "Nd3"),
+        (KNIGHTS, "Ke3"),
+        (KNIGHTS, "O-O"),
+        (ROOKS, "Rf1"),
+        (CASTLING, "e5"),
+        (PROMOTION, "d8=Q"),
+    ];
+    for (fen, san) in illegal {
+        let board = Board::from_fen(fen).unwrap();
+        let error = MoveParseError::Illegal(san.to_string());
+        assert_eq!(Move::from_san(san, &board), Err(error), "{fen} {san}");
+    }
+
+    for san in ["", "Nz9", "Nbd2d2", "N"] {
+        let board = Board::from_fen(KNIGHTS).unwrap();
+        let error = MoveParseError::InvalidSan(san.to_string());
+        assert_eq!(Move::from_san(san, &board), Err(error), "{san}");
+    }
+
+    let board = Board::from_fen(PROMOTION).unwrap();
+    let error = MoveParseError::InvalidPromotion('K');
+    assert_eq!(Move::from_san("e8=K", &board), Err(error));
+} //
+
+/// Every legal move survives the way to SAN and back.
+#[test]
+fn every_legal_move_round_trips() {
+    init();
+
+    for fen in [
+        KNIGHTS,
+        QUEENS,
+        CASTLING,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    ] {
+        let mut board = Board::from_fen(fen).unwrap();
+
+        for mv in board.generate_moves() {
+            let san = mv.to_san(&board);
+            assert_eq!(Move::from_san(&san, &board), Ok(mv), "{fen} {san}");
+        }
+    }
+} //
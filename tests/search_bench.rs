@@ -0,0 +1,161 @@
+mod common;
+
+use common::{engine, init};
+use queenfish::board::search::MATE_SCORE;
+use queenfish::board::tt::{Bound, TranspositionTable, score_from_tt, score_to_tt};
+use queenfish::board::{Board, SearchConfig, SearchLimits};
+
+const BENCH: [(&str, i32); 5] = [
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 8),
+    ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 8),
+    ("r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10", 4),
+    ("6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1", 7),
+    ("8/8/4k3/8/2p5/8/B2K4/8 w - - 0 1", 9),
+];
+
+fn bench_nodes(config: SearchConfig) -> u64 {
+    let mut engine = engine(config);
+
+    BENCH
+        .iter()
+        .map(|(fen, depth)| {
+            engine.new_game();
+            let board = Board::from_fen(fen).unwrap();
+            engine.search(&board, &SearchLimits::depth(*depth)).nodes
+        })
+        .sum()
+} //
+
+#[test]
+fn tt_reduces_bench_nodes() {
+    init();
+
+    let with_tt = bench_nodes(SearchConfig::default());
+    let without_tt = bench_nodes(SearchConfig {
+        tt: false,
+        ..Default::default()
+    });
+
+    println!("bench nodes: {with_tt} with tt, {without_tt} without");
+    assert!(with_tt < without_tt);
+} //
+
+#[test]
+fn mate_distance_survives_the_tt() {
+    init();
+
+    // Ra7 or Rb7, then mate on the back rank
+    let board = Board::from_fen("7k/8/8/8/8/8/1R6/R5K1 w - - 0 1").unwrap();
+    let mut engine = engine(SearchConfig::default());
+
+    // The second search starts from the entries of the first one
+    for _ in 0..2 {
+        let result = engine.search(&board, &SearchLimits::depth(6));
+        assert_eq!(result.score, MATE_SCORE - 3);
+    }
+} //
+
+/// `go mate n` finds mates of exactly `n` moves, not only shorter ones.
+#[test]
+fn mate_limit_reaches_the_mated_side() {
+    let mut engine = engine(SearchConfig::default());
+
+    // (fen, mate in)
+    for (fen, mate) in [
+        ("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 1),
+        ("7k/8/8/8/8/8/1R6/R5K1 w - - 0 1", 2),
+    ] {
+        let board = Board::from_fen(fen).unwrap();
+        let limits = SearchLimits {
+            mate: Some(mate),
+            ..Default::default()
+        };
+        let result = engine.search(&board, &limits);
+
+        assert_eq!(result.score, MATE_SCORE - (2 * mate as i32 - 1), "{fen}");
+        assert_eq!(result.depth, 2 * mate as i32, "{fen}");
+    }
+} //
+
+/// Depths past `MAX_DEPTH` are searched to `MAX_DEPTH`, where the per-ply tables end.
+#[test]
+fn depth_is_clamped_to_max_depth() {
+    let mut engine = engine(SearchConfig::default());
+
+    for limits in [
+        SearchLimits::depth(1000),
+        SearchLimits {
+            depth: Some(1000),
+            mate: Some(1000),
+            ..Default::default()
+        },
+    ] {
+        assert_eq!(limits.max_depth(), SearchLimits::MAX_DEPTH);
+
+        // Every line is a draw at once, so all the iterations are quick
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let result = engine.search(&board, &limits);
+        assert_eq!(result.depth, SearchLimits::MAX_DEPTH);
+        assert_eq!(result.score, 0);
+    }
+} //
+
+/// Bounds cut off only on their side of the window, exact scores always, and
+/// nothing cuts off below the depth it was searched to.
+#[test]
+fn tt_bounds_cut_off_outside_the_window() {
+    let tt = TranspositionTable::new(1);
+    let key = 0x0F1E_2D3C_4B5A_6978;
+    let (alpha, beta) = (-50, 50);
+
+    // (score, bound, cutoff within (alpha, beta))
+    let cases = [
+        (50, Bound::Lower, Some(50)),
+        (120, Bound::Lower, Some(120)),
+        (49, Bound::Lower, None),
+        (-50, Bound::Upper, Some(-50)),
+        (-120, Bound::Upper, Some(-120)),
+        (-49, Bound::Upper, None),
+        (10, Bound::Exact, Some(10)),
+    ];
+
+    for (score, bound, expected) in cases {
+        tt.store(key, 4, score, bound, None);
+        let entry = tt.probe(key).unwrap();
+
+        assert_eq!(entry.cutoff(4, alpha, beta, 0), expected, "{score}");
+        assert_eq!(entry.cutoff(3, alpha, beta, 0), expected, "{score}");
+        assert_eq!(entry.cutoff(5, alpha, beta, 0), None, "{score}");
+    }
+
+    // A mate in 5 plies from the node is a mate in 15 from the root at ply 10
+    tt.store(key, 4, score_to_tt(MATE_SCORE - 15, 10), Bound::Lower, None);
+    let entry = tt.probe(key).unwrap();
+    assert_eq!(entry.cutoff(4, alpha, beta, 10), Some(MATE_SCORE - 15));
+    assert_eq!(entry.cutoff(4, alpha, beta, 2), Some(MATE_SCORE - 7));
+} //
+
+/// A mate score failing low by one at ply 10 is still an upper bound, although the
+/// node relative score stored in the table is above alpha.
+#[test]
+fn mate_scores_keep_their_bound_in_the_tt() {
+    let tt = TranspositionTable::new(1);
+    let ply = 10;
+    let key = 0x1234_5678_9ABC_DEF0;
+
+    // (score, alpha, beta, expected bound)
+    let cases = [
+        (MATE_SCORE - 21, MATE_SCORE - 20, MATE_SCORE - 5, Bound::Upper),
+        (-MATE_SCORE + 21, -MATE_SCORE + 5, -MATE_SCORE + 20, Bound::Lower),
+    ];
+
+    for (score, alpha, beta, expected) in cases {
+        let bound = Bound::new(score, alpha, beta, true);
+        assert!(bound == expected, "{score} in ({alpha}, {beta})");
+
+        tt.store(key, 4, score_to_tt(score, ply), bound, None);
+        let entry = tt.probe(key).unwrap();
+        assert!(entry.bound == expected, "{score} in ({alpha}, {beta})");
+        assert_eq!(score_from_tt(entry.score, ply), score);
+    }
+} //
@@ -0,0 +1,59 @@
+mod common;
+
+use common::engine;
+use queenfish::board::{Board, SearchConfig, SearchLimits, SearchRunning};
+use std::thread;
+use std::time::Duration;
+
+/// Starting a search while an infinite one runs stops the first one instead of
+/// waiting for it forever.
+#[test]
+fn second_start_search_stops_the_first() {
+    let engine = engine(SearchConfig::default());
+    let mut board = Board::new();
+    let legal = board.generate_moves();
+
+    let first = engine.start_search(board.clone(), SearchLimits::infinite());
+    thread::sleep(Duration::from_millis(50));
+    let second = engine.start_search(board.clone(), SearchLimits::depth(3));
+
+    let first = first.join();
+    assert!(legal.contains(&first.best_move.unwrap()));
+
+    let second = second.join();
+    assert_eq!(second.depth, 3);
+    assert!(legal.contains(&second.best_move.unwrap()));
+} //
+
+/// Back to back starts with no time for the threads to take the searcher.
+#[test]
+fn repeated_start_search_never_hangs() {
+    let engine = engine(SearchConfig::default());
+    let board = Board::new();
+
+    let handles: Vec<_> = (0..8)
+        .map(|_| engine.start_search(board.clone(), SearchLimits::infinite()))
+        .collect();
+    let last = engine.start_search(board.clone(), SearchLimits::depth(2));
+
+    for handle in handles {
+        handle.join();
+    }
+    assert_eq!(last.join().depth, 2);
+} //
+
+#[test]
+fn settings_are_refused_while_searching() {
+    let mut engine = engine(SearchConfig::default());
+    let search = engine.start_search(Board::new(), SearchLimits::infinite());
+
+    assert_eq!(engine.set_threads(2), Err(SearchRunning));
+    assert_eq!(engine.set_hash_size(1), Err(SearchRunning));
+
+    search.stop();
+    search.join();
+    assert_eq!(engine.set_threads(2), Ok(()));
+    assert_eq!(engine.set_hash_size(1), Ok(()));
+    let result = engine.search(&Board::new(), &SearchLimits::depth(3));
+    assert_eq!(result.depth, 3);
+} //
@@ -0,0 +1,46 @@
+mod common;
+
+use common::init;
+use queenfish::board::{Board, Move};
+
+// (fen, move, exchange value)
+const EXCHANGES: [(&str, &str, i32); 9] = [
+    // Undefended pawn
+    (
+        "1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1",
+        "e1e5",
+        100,
+    ),
+    // Knight takes a pawn, the queen x-rays through the bishop
+    (
+        "1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1",
+        "d3e5",
+        -200,
+    ),
+    // Queen takes a pawn defended by a pawn
+    ("4k3/8/3p4/4p3/8/8/4Q3/4K3 w - - 0 1", "e2e5", -800),
+    // Rooks doubled behind each other win the pawn
+    ("4k3/4r3/8/4p3/8/8/4R3/4R1K1 w - - 0 1", "e2e5", 100),
+    // The king recaptures, unless the rook is defended
+    ("8/8/8/3kp3/8/8/8/4RK2 w - - 0 1", "e1e5", -400),
+    ("8/8/8/3kp3/8/8/4R3/4RK2 w - - 0 1", "e2e5", 100),
+    // En passant
+    ("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6", 100),
+    // Quiet moves, onto a square attacked by a pawn and a safe one
+    ("4k3/8/8/4p3/8/8/8/2B1K3 w - - 0 1", "c1f4", -300),
+    ("4k3/8/8/4p3/8/8/8/2B1K3 w - - 0 1", "c1d2", 0),
+];
+
+#[test]
+fn see_matches_hand_counted_exchanges() {
+    init();
+
+    for (fen, uci, value) in EXCHANGES {
+        let board = Board::from_fen(fen).unwrap();
+        let mv = Move::parse_uci(uci, &board).unwrap();
+
+        assert_eq!(board.see(mv), value, "{fen} {uci}");
+        assert!(board.see_ge(mv, value), "{fen} {uci}");
+        assert!(!board.see_ge(mv, value + 1), "{fen} {uci}");
+    }
+} //
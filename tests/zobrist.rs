@@ -0,0 +1,83 @@
+mod common;
+
+use common::init;
+use queenfish::board::{Board, Move};
+
+/// White can take d5 en passant.
+const CAPTURABLE: &str = "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2";
+/// No white pawn next to d5.
+const NOT_CAPTURABLE: &str = "4k3/8/8/3p4/8/8/4P3/4K3 w - d6 0 2";
+
+fn hash(fen: &str) -> u64 {
+    let board = Board::from_fen(fen).unwrap();
+    assert_eq!(board.hash, board.compute_hash(), "{fen}");
+    board.hash
+}
+
+fn play(board: &mut Board, moves: &[&str]) {
+    for uci in moves {
+        let mv = Move::parse_uci(uci, board).unwrap();
+        board.make_move(mv);
+        assert_eq!(board.hash, board.compute_hash(), "{uci}");
+    }
+}
+
+#[test]
+fn capturable_en_passant_square_is_hashed() {
+    init();
+
+    let without = CAPTURABLE.replace(" d6 ", " - ");
+    assert_ne!(hash(CAPTURABLE), hash(&without));
+
+    // The double push sets the square, the same position reached with two single
+    // pushes has none
+    let mut board = Board::from_fen("4k3/3p4/8/4P3/8/8/8/4K3 b - - 0 1").unwrap();
+    play(&mut board, &["d7d5"]);
+    assert_eq!(board.hash, hash(CAPTURABLE));
+
+    let mut board = Board::from_fen("4k3/3p4/8/4P3/8/8/8/4K3 b - - 0 1").unwrap();
+    play(
+        &mut board,
+        &["d7d6", "e1d1", "e8d8", "d1e1", "d8e8", "e1d1", "d6d5"],
+    );
+    assert_eq!(board.hash, hash("4k3/8/8/3pP3/8/8/8/3K4 w - - 0 5"));
+    assert_ne!(board.hash, hash("4k3/8/8/3pP3/8/8/8/3K4 w - d6 0 5"));
+} //
+
+#[test]
+fn uncapturable_en_passant_square_hashes_like_none() {
+    init();
+
+    let without = NOT_CAPTURABLE.replace(" d6 ", " - ");
+    assert_eq!(hash(NOT_CAPTURABLE), hash(&without));
+
+    let mut board = Board::from_fen("4k3/3p4/8/8/8/8/4P3/4K3 b - - 0 1").unwrap();
+    play(&mut board, &["d7d5"]);
+    assert_eq!(board.hash, hash(&without));
+
+    // Only a pawn of the side to move counts
+    let black_pawn = "4k3/8/8/3pp3/8/8/8/4K3 w - d6 0 2";
+    assert_eq!(hash(black_pawn), hash(&black_pawn.replace(" d6 ", " - ")));
+} //
+
+#[test]
+fn make_unmake_and_null_move_restore_the_hash() {
+    init();
+
+    let mut board = Board::from_fen(CAPTURABLE).unwrap();
+    let before = board.hash;
+
+    for mv in board.generate_moves() {
+        let undo = board.make_move(mv);
+        assert_eq!(board.hash, board.compute_hash(), "{}", mv.to_uci());
+        board.unmake_move(undo);
+        assert_eq!(board.hash, before, "{}", mv.to_uci());
+    }
+
+    // The null move gives up the capture
+    let en_passant = board.make_null_move();
+    assert_eq!(board.hash, board.compute_hash());
+    assert_eq!(board.hash, hash("4k3/8/8/3pP3/8/8/8/4K3 b - - 0 2"));
+    board.unmake_null_move(en_passant);
+    assert_eq!(board.hash, before);
+} //